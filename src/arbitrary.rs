@@ -0,0 +1,73 @@
+//! Property-testing support for Pagerank, enabled with the `proptest` feature.
+//!
+//! The strategies in this module generate valid Pagerank instances whose size is bounded
+//! by the caller, so downstream crates can property-test their own ranking pipelines
+//! (e.g. "scores always sum to 1") without hand-writing graph generators.
+use crate::Pagerank;
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+/// Bounds used when generating arbitrary Pagerank instances.
+///
+/// Fields:
+/// - max_nodes: The maximum number of nodes (and therefore capacity) of a generated graph.
+///   Must be at least 1.
+/// - max_links: The maximum number of links added to a generated graph.
+#[derive(Debug, Clone, Copy)]
+pub struct PagerankParams {
+    pub max_nodes: usize,
+    pub max_links: usize,
+}
+
+impl Default for PagerankParams {
+    fn default() -> Self {
+        PagerankParams {
+            max_nodes: 32,
+            max_links: 128,
+        }
+    }
+}
+
+/// Returns a strategy generating Pagerank instances with up to max_nodes nodes and up to
+/// max_links links.
+///
+/// Node keys are drawn from 0..capacity, so every generated link fits within the capacity
+/// of the generated instance. Self links and duplicate links may be generated.
+///
+/// # Panics
+///
+/// Panics if max_nodes is 0.
+///
+/// # Examples
+///
+/// proptest! {
+///     #[test]
+///     fn scores_sum_to_one(mut pagerank in pagerank_strategy(10, 40)) {
+///         let total: f64 = pagerank.rank(0.85, 1e-6).iter().map(|(_, rank)| rank).sum();
+///         // ...
+///     }
+/// }
+pub fn pagerank_strategy(max_nodes: usize, max_links: usize) -> impl Strategy<Value = Pagerank> {
+    assert!(max_nodes > 0, "max_nodes must be at least 1");
+
+    (1..=max_nodes).prop_flat_map(move |capacity| {
+        vec((0..capacity, 0..capacity), 0..=max_links).prop_map(move |links| {
+            let mut pagerank = Pagerank::new(capacity);
+            for (from, to) in links {
+                pagerank
+                    .link(from, to)
+                    .expect("generated links always fit within the capacity");
+            }
+            pagerank
+        })
+    })
+}
+
+impl Arbitrary for Pagerank {
+    type Parameters = PagerankParams;
+    type Strategy = BoxedStrategy<Pagerank>;
+
+    fn arbitrary_with(params: Self::Parameters) -> Self::Strategy {
+        pagerank_strategy(params.max_nodes, params.max_links).boxed()
+    }
+}
@@ -0,0 +1,178 @@
+//! This module provides the scaffolding of the crate's benchmarks: seeded graph generators for
+//! standard graph shapes, timing of rank computations and helpers comparing rank results, so
+//! damping factor and tolerance settings can be evaluated on any hardware.
+//!
+//! Only available with the `bench_utils` feature.
+use crate::config::RankConfig;
+use crate::Pagerank;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+use std::time::{Duration, Instant};
+
+const WITHIN_CAPACITY: &str = "generated links stay within the capacity";
+
+/// Generates the links of a graph where each of the nodes links to a uniformly random number of
+/// targets in 0..max_out_links, chosen uniformly. This is the graph of the crate's benchmarks,
+/// as an edge list so other libraries can rank the same graph.
+///
+/// # Arguments
+///
+/// * nodes - The number of nodes, with keys in 0..nodes.
+/// * max_out_links - The exclusive upper bound of the number of links of each node.
+/// * seed - The seed of the generator, the same seed always generates the same links.
+///
+/// # Examples
+///
+/// let edges = uniform_random_edges(100_000, 400, 42);
+///
+pub fn uniform_random_edges(nodes: usize, max_out_links: usize, seed: u64) -> Vec<(usize, usize)> {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut edges = Vec::new();
+    if max_out_links == 0 {
+        return edges;
+    }
+
+    for from in 0..nodes {
+        for _ in 0..rng.gen_range(0..max_out_links) {
+            edges.push((from, rng.gen_range(0..nodes)));
+        }
+    }
+    edges
+}
+
+/// Generates the graph of the links of uniform_random_edges.
+///
+/// # Arguments
+///
+/// * nodes - The number of nodes, with keys in 0..nodes.
+/// * max_out_links - The exclusive upper bound of the number of links of each node.
+/// * seed - The seed of the generator, the same seed always generates the same graph.
+///
+/// # Examples
+///
+/// let pagerank = uniform_random_graph(100_000, 400, 42);
+///
+pub fn uniform_random_graph(nodes: usize, max_out_links: usize, seed: u64) -> Pagerank {
+    let mut graph = Pagerank::new(nodes);
+    for (from, to) in uniform_random_edges(nodes, max_out_links, seed) {
+        graph.link(from, to).expect(WITHIN_CAPACITY);
+    }
+    graph
+}
+
+/// Generates a scale-free graph by preferential attachment: every new node links to
+/// links_per_node existing nodes chosen proportionally to the number of links they take part
+/// in, producing the skewed degree distribution of social and web graphs.
+///
+/// # Arguments
+///
+/// * nodes - The number of nodes, with keys in 0..nodes.
+/// * links_per_node - The number of links added with each node.
+/// * seed - The seed of the generator, the same seed always generates the same graph.
+pub fn preferential_attachment_graph(nodes: usize, links_per_node: usize, seed: u64) -> Pagerank {
+    let mut rng = StdRng::seed_from_u64(seed);
+    let mut graph = Pagerank::new(nodes);
+    // Every node appears once per link it takes part in, so uniform picks are degree biased.
+    let mut endpoints: Vec<usize> = Vec::with_capacity(2 * nodes * links_per_node);
+
+    for from in 1..nodes {
+        for _ in 0..links_per_node {
+            let to = if endpoints.is_empty() {
+                rng.gen_range(0..from)
+            } else {
+                endpoints[rng.gen_range(0..endpoints.len())]
+            };
+            graph.link(from, to).expect(WITHIN_CAPACITY);
+            endpoints.push(to);
+        }
+        endpoints.push(from);
+    }
+    graph
+}
+
+/// Generates a directed ring where node i links to node (i + 1) % nodes. Every node has the
+/// same score.
+pub fn ring_graph(nodes: usize) -> Pagerank {
+    let mut graph = Pagerank::new(nodes);
+    for from in 0..nodes {
+        graph.link(from, (from + 1) % nodes).expect(WITHIN_CAPACITY);
+    }
+    graph
+}
+
+/// Generates a star where the leaves 1..nodes link to the hub 0 and the hub links back to
+/// every leaf.
+pub fn star_graph(nodes: usize) -> Pagerank {
+    let mut graph = Pagerank::new(nodes);
+    for leaf in 1..nodes {
+        graph.link(leaf, 0).expect(WITHIN_CAPACITY);
+        graph.link(0, leaf).expect(WITHIN_CAPACITY);
+    }
+    graph
+}
+
+/// The outcome of a timed rank computation.
+///
+/// Fields:
+/// - ranked: The scores, sorted by descending score.
+/// - iterations: The number of iterations until convergence.
+/// - elapsed: The wall time of the computation.
+#[derive(Debug, Clone)]
+pub struct Measurement {
+    pub ranked: Vec<(usize, f64)>,
+    pub iterations: usize,
+    pub elapsed: Duration,
+}
+
+/// Ranks graph with config, measuring the wall time and the number of iterations.
+///
+/// # Examples
+///
+/// let graph = preferential_attachment_graph(1_000_000, 8, 42);
+/// for tolerance in [1e-4, 1e-6, 1e-8] {
+///     let measurement = measure(&graph, &RankConfig::new(0.85, tolerance));
+///     println!("{}: {} iterations in {:?}", tolerance, measurement.iterations, measurement.elapsed);
+/// }
+///
+pub fn measure(graph: &Pagerank, config: &RankConfig) -> Measurement {
+    let started = Instant::now();
+    let result = graph.rank_budgeted(config, usize::MAX);
+    Measurement {
+        ranked: result.ranked,
+        iterations: result.iterations,
+        elapsed: started.elapsed(),
+    }
+}
+
+/// Returns the largest absolute difference between the scores of a node in two rank results.
+/// Nodes missing from a result count as a score of 0.
+pub fn max_score_difference(a: &[(usize, f64)], b: &[(usize, f64)]) -> f64 {
+    let b_scores: HashMap<usize, f64> = b.iter().copied().collect();
+    let a_keys: HashMap<usize, f64> = a.iter().copied().collect();
+
+    a.iter()
+        .map(|(key, score)| (score - b_scores.get(key).copied().unwrap_or(0.0)).abs())
+        .chain(
+            b.iter()
+                .filter(|(key, _)| !a_keys.contains_key(key))
+                .map(|(_, score)| score.abs()),
+        )
+        .fold(0.0, f64::max)
+}
+
+/// Returns the fraction of the first k nodes of a that are also among the first k nodes of b,
+/// e.g. to check whether a looser tolerance changes the top results. Returns 1 if k is 0.
+pub fn top_k_overlap(a: &[(usize, f64)], b: &[(usize, f64)], k: usize) -> f64 {
+    if k == 0 {
+        return 1.0;
+    }
+
+    let b_top: HashMap<usize, f64> = b.iter().take(k).copied().collect();
+    let shared = a
+        .iter()
+        .take(k)
+        .filter(|(key, _)| b_top.contains_key(key))
+        .count();
+    shared as f64 / k as f64
+}
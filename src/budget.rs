@@ -0,0 +1,96 @@
+//! This module provides anytime rank computations, which run as many iterations as fit in a time
+//! or work budget and return the best scores reached so far together with an estimate of their
+//! quality, for serving rankings under a latency bound.
+use crate::config::RankConfig;
+use crate::Pagerank;
+use std::time::{Duration, Instant};
+
+/// The outcome of a rank computation limited by a budget.
+///
+/// Fields:
+/// - ranked: The scores after the last completed iteration, sorted by descending score.
+/// - iterations: The number of completed iterations.
+/// - change: The change in scores of the last iteration, 2 (the maximum) if none completed.
+/// - error_bound: An estimate of the distance between ranked and the converged scores, as the
+///   sum of the absolute differences of the scores, at most 2. Derived from the damping factor
+///   and change with PowerIteration, from one more power iteration step with GaussSeidel and
+///   Condensation, and 0 once Exact solved the graph.
+/// - converged: Whether change fell below the tolerance within the budget.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BudgetedRank {
+    pub ranked: Vec<(usize, f64)>,
+    pub iterations: usize,
+    pub change: f64,
+    pub error_bound: f64,
+    pub converged: bool,
+}
+
+impl Pagerank {
+    /// Computes the PageRank scores, stopping early when the next iteration would not complete
+    /// within budget.
+    ///
+    /// The duration of the next iteration is predicted from the previous one. The time needed to
+    /// prepare the computation counts towards the budget. With GaussSeidel and Condensation, the
+    /// error_bound of the result takes one more iteration on top of the budget.
+    ///
+    /// # Arguments
+    ///
+    /// * config - The parameters of the computation.
+    /// * budget - The maximum duration of the computation.
+    ///
+    /// # Examples
+    ///
+    /// let result = pagerank.rank_for(&RankConfig::default(), Duration::from_millis(20));
+    /// if result.error_bound > 0.01 {
+    ///     log::warn!("Serving approximate scores");
+    /// }
+    ///
+    pub fn rank_for(&self, config: &RankConfig, budget: Duration) -> BudgetedRank {
+        let started = Instant::now();
+        let mut iteration_started: Option<Instant> = None;
+
+        self.rank_within_budget(config, move |_| {
+            let now = Instant::now();
+            let last_iteration = iteration_started.map_or(Duration::ZERO, |start| now - start);
+            iteration_started = Some(now);
+            now - started + last_iteration < budget
+        })
+    }
+
+    /// Computes the PageRank scores, stopping early when the next iteration would exceed
+    /// max_edge_visits.
+    ///
+    /// Every iteration visits each node and each link once. Unlike rank_for, the result only
+    /// depends on the graph and the arguments, not on the load of the machine.
+    ///
+    /// # Arguments
+    ///
+    /// * config - The parameters of the computation.
+    /// * max_edge_visits - The maximum number of node and link visits of the computation.
+    pub fn rank_budgeted(&self, config: &RankConfig, max_edge_visits: usize) -> BudgetedRank {
+        let visits_per_iteration = self.visits_per_iteration();
+
+        self.rank_within_budget(config, move |iterations| {
+            (iterations + 1).saturating_mul(visits_per_iteration) <= max_edge_visits
+        })
+    }
+
+    fn rank_within_budget<B>(&self, config: &RankConfig, within_budget: B) -> BudgetedRank
+    where
+        B: FnMut(usize) -> bool + Send,
+    {
+        Self::install(config, || {
+            let setup = self.setup(config, None);
+            let (p, iterations, change, solver) = self.iterate(config, &setup, within_budget);
+            let error_bound = self.error_bound(config, &setup, solver, &p, iterations, change);
+
+            BudgetedRank {
+                ranked: self.emit(config, p).ranked,
+                iterations,
+                change,
+                error_bound,
+                converged: change <= config.tolerance,
+            }
+        })
+    }
+}
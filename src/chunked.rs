@@ -0,0 +1,504 @@
+//! This module provides a chunked mode of the PageRank computation for single-threaded
+//! environments such as wasm running on a browser's UI thread.
+//!
+//! Every pass over the nodes, from deriving the per node data of the computation to sorting the
+//! result, is processed sequentially in chunks on the calling thread without using the rayon
+//! thread pool, and control is handed back to the caller between chunks so it can yield to its
+//! event loop.
+use crate::config::{InitialDistribution, RankConfig, TieBreak};
+use crate::pagerank::RankSetup;
+use crate::Pagerank;
+use std::cmp::Ordering;
+use std::future::Future;
+
+/// The stages of a chunked computation, in order. Each stage is a pass of which chunk_size
+/// items are processed per call to process_chunk.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum Stage {
+    /// Deriving the share of each link, the teleport and the unnormalized initial scores.
+    Setup,
+    /// Copying the scores of a warm start, the items are the entries of the warm start.
+    WarmStart,
+    /// Normalizing the teleport and the initial scores.
+    Normalize,
+    /// Computing the unnormalized scores of the iteration in progress.
+    Iterate,
+    /// Normalizing the scores of the iteration in progress and measuring their change.
+    Converge,
+    /// Collecting the emitted (key, score) pairs.
+    Collect,
+    /// Sorting runs of chunk_size pairs of the sorted range, one run per chunk.
+    Sort,
+    /// Merging pairs of sorted runs of width pairs of the sorted range into merged.
+    Merge {
+        width: usize,
+    },
+    /// Copying the merged pairs back into the sorted range, when it is not the whole result.
+    CopyBack {
+        width: usize,
+    },
+    /// Looking for the runs of tied scores, each run is then sorted by the tie-break.
+    Ties,
+    Done,
+}
+
+/// A PageRank computation that advances one chunk of nodes at a time.
+///
+/// Created with Pagerank::rank_chunked. The caller drives the computation by calling
+/// process_chunk until it returns true, and then collects the scores with into_ranked.
+///
+/// Fields:
+/// - graph: The graph being ranked.
+/// - config: The parameters of the computation.
+/// - setup: The per computation data derived from the graph and the configuration.
+/// - p: The scores of the last completed iteration, swapped with new_p once the iteration in
+///   progress is computed so they are normalized in place.
+/// - new_p: The scores of the iteration in progress.
+/// - chunk_size: The maximum number of items processed per chunk.
+/// - stage: The pass in progress.
+/// - next_index: The first item of the next chunk of the pass in progress.
+/// - source: The index of the source node of an InitialDistribution::Source.
+/// - receivers: The sum of the unnormalized teleport.
+/// - sum: The sum of the unnormalized scores of the pass in progress.
+/// - dangling_sum: The score held by dangling nodes in the last completed iteration.
+/// - next_dangling_sum: The score held by dangling nodes in the iteration in progress.
+/// - change: The change in scores of the last completed iteration.
+/// - next_change: The change in scores of the iteration in progress.
+/// - ranked: The emitted (key, score) pairs.
+/// - sort_start: The first pair of the range being sorted.
+/// - sort_end: The end of the range being sorted.
+/// - by_tie_order: Whether the range is a run of ties sorted by the tie-break, instead of
+///   pairs sorted by score.
+/// - merged: The pairs merged so far by the merge pass in progress.
+/// - left: The next pair of the left run of the merge in progress.
+/// - right: The next pair of the right run of the merge in progress.
+/// - tie_start: The first pair of the run of ties in progress.
+pub struct ChunkedRank<'a> {
+    graph: &'a Pagerank,
+    config: RankConfig,
+    setup: RankSetup,
+    p: Vec<f64>,
+    new_p: Vec<f64>,
+    chunk_size: usize,
+    stage: Stage,
+    next_index: usize,
+    source: Option<usize>,
+    receivers: f64,
+    sum: f64,
+    dangling_sum: f64,
+    next_dangling_sum: f64,
+    change: f64,
+    next_change: f64,
+    ranked: Vec<(usize, f64)>,
+    sort_start: usize,
+    sort_end: usize,
+    by_tie_order: bool,
+    merged: Vec<(usize, f64)>,
+    left: usize,
+    right: usize,
+    tie_start: usize,
+}
+
+impl<'a> ChunkedRank<'a> {
+    fn new(graph: &'a Pagerank, config: &RankConfig, chunk_size: usize) -> ChunkedRank<'a> {
+        let size = graph.size();
+        let source = graph.initial_source(config);
+
+        ChunkedRank {
+            graph,
+            config: config.clone(),
+            setup: RankSetup {
+                inverse_out_links: Vec::with_capacity(size),
+                dangling_nodes: Vec::new(),
+                teleport: Vec::with_capacity(size),
+                allowed_edge_types: None,
+                reversed: false,
+            },
+            p: Vec::with_capacity(size),
+            new_p: vec![0.0; size],
+            chunk_size: chunk_size.max(1),
+            stage: Stage::Setup,
+            next_index: 0,
+            source,
+            receivers: 0.0,
+            sum: 0.0,
+            dangling_sum: 0.0,
+            next_dangling_sum: 0.0,
+            change: 2.0,
+            next_change: 0.0,
+            ranked: Vec::new(),
+            sort_start: 0,
+            sort_end: 0,
+            by_tie_order: false,
+            merged: Vec::new(),
+            left: 0,
+            right: 0,
+            tie_start: 0,
+        }
+    }
+
+    /// Returns whether the computation has converged.
+    pub fn is_converged(&self) -> bool {
+        self.change <= self.config.tolerance
+    }
+
+    /// Processes the next chunk of the computation.
+    ///
+    /// Returns true once the sorted result is ready to be collected with into_ranked, after which
+    /// further calls do nothing.
+    pub fn process_chunk(&mut self) -> bool {
+        let sort_len = self.sort_end - self.sort_start;
+        let (items, step) = match self.stage {
+            Stage::WarmStart => match &self.config.initial {
+                InitialDistribution::WarmStart(previous) => (previous.len(), self.chunk_size),
+                _ => (0, self.chunk_size),
+            },
+            // A run holds chunk_size pairs, so a chunk sorts a single run.
+            Stage::Sort => (sort_len.div_ceil(self.chunk_size), 1),
+            Stage::Merge { .. } | Stage::CopyBack { .. } => (sort_len, self.chunk_size),
+            Stage::Ties => (self.ranked.len(), self.chunk_size),
+            Stage::Done => return true,
+            _ => (self.graph.size(), self.chunk_size),
+        };
+        let start = self.next_index;
+        let end = (start + step).min(items);
+
+        match self.stage {
+            Stage::Setup => self.setup_nodes(start..end),
+            Stage::WarmStart => self.warm_start(start..end),
+            Stage::Normalize => self.normalize_nodes(start..end),
+            Stage::Iterate => self.iterate_nodes(start..end),
+            Stage::Converge => self.converge_nodes(start..end),
+            Stage::Collect => self.collect_nodes(start..end),
+            Stage::Sort => self.sort_runs(start..end),
+            Stage::Merge { width } => self.merge_pairs(width, start..end),
+            Stage::CopyBack { .. } => self.copy_back(start..end),
+            Stage::Ties => {
+                if let Some(run_end) = self.find_tie_run(start..end) {
+                    // The run is sorted by the following chunks, the search resumes at its end.
+                    self.stage = self.begin_sort(self.tie_start, run_end, true);
+                    self.tie_start = run_end;
+                    self.next_index = 0;
+                    return false;
+                }
+            }
+            Stage::Done => {}
+        }
+        self.next_index = end;
+
+        if end == items {
+            self.finish_stage();
+        }
+        self.stage == Stage::Done
+    }
+
+    fn finish_stage(&mut self) {
+        self.next_index = 0;
+        if self.stage == Stage::Converge {
+            self.change = self.next_change;
+        }
+        self.stage = match self.stage {
+            Stage::Setup => match self.config.initial {
+                InitialDistribution::WarmStart(_) => Stage::WarmStart,
+                _ => Stage::Normalize,
+            },
+            Stage::WarmStart => Stage::Normalize,
+            Stage::Normalize | Stage::Converge if !self.is_converged() => {
+                self.sum = 0.0;
+                self.dangling_sum = self.next_dangling_sum;
+                Stage::Iterate
+            }
+            Stage::Normalize | Stage::Converge => Stage::Collect,
+            Stage::Iterate => {
+                // The new scores are normalized in p, new_p keeps the previous ones for the change.
+                std::mem::swap(&mut self.p, &mut self.new_p);
+                self.next_change = 0.0;
+                self.next_dangling_sum = 0.0;
+                Stage::Converge
+            }
+            Stage::Collect => self.begin_sort(0, self.ranked.len(), false),
+            Stage::Sort if self.sort_end - self.sort_start > self.chunk_size => {
+                self.merged = Vec::with_capacity(self.sort_end - self.sort_start);
+                Stage::Merge {
+                    width: self.chunk_size,
+                }
+            }
+            Stage::Sort => self.sorted(),
+            Stage::Merge { width }
+                if self.sort_start == 0 && self.sort_end == self.ranked.len() =>
+            {
+                std::mem::swap(&mut self.ranked, &mut self.merged);
+                self.merge_pass_done(width)
+            }
+            Stage::Merge { width } => Stage::CopyBack { width },
+            Stage::CopyBack { width } => self.merge_pass_done(width),
+            Stage::Ties if self.ranked.len() - self.tie_start > 1 => {
+                let end = self.ranked.len();
+                let stage = self.begin_sort(self.tie_start, end, true);
+                self.tie_start = end;
+                stage
+            }
+            Stage::Ties => Stage::Done,
+            Stage::Done => Stage::Done,
+        };
+    }
+
+    fn setup_nodes(&mut self, nodes: std::ops::Range<usize>) {
+        for i in nodes {
+            let key = self.graph.key_of(i);
+            let inverse_out_links = Pagerank::inverse_out_links_of(
+                &self.config,
+                key,
+                self.graph.number_out_links_of(i),
+            );
+            if inverse_out_links == 0.0 {
+                self.setup.dangling_nodes.push(i);
+            }
+            self.setup.inverse_out_links.push(inverse_out_links);
+
+            let teleport = Pagerank::unnormalized_teleport(&self.config, key);
+            self.receivers += teleport;
+            self.setup.teleport.push(teleport);
+
+            let p_i = self
+                .graph
+                .unnormalized_initial_score(&self.config, self.source, i);
+            self.sum += p_i;
+            self.p.push(p_i);
+        }
+    }
+
+    fn warm_start(&mut self, entries: std::ops::Range<usize>) {
+        let InitialDistribution::WarmStart(previous) = &self.config.initial else {
+            return;
+        };
+        for &(key, score) in &previous[entries] {
+            if let Some(index) = self.graph.index_of(key) {
+                self.sum += score - self.p[index];
+                self.p[index] = score;
+            }
+        }
+    }
+
+    fn normalize_nodes(&mut self, nodes: std::ops::Range<usize>) {
+        let size = self.graph.size();
+        for i in nodes {
+            self.setup.teleport[i] =
+                Pagerank::normalized_teleport(self.setup.teleport[i], self.receivers, size);
+            self.p[i] = Pagerank::normalized_initial_score(
+                &self.config,
+                self.p[i],
+                self.sum,
+                self.setup.teleport[i],
+                size,
+            );
+            if self.setup.inverse_out_links[i] == 0.0 {
+                self.next_dangling_sum += self.p[i];
+            }
+        }
+    }
+
+    fn iterate_nodes(&mut self, nodes: std::ops::Range<usize>) {
+        for i in nodes {
+            self.new_p[i] = self.graph.sequential_node_rank(
+                self.config.following_prob,
+                &self.setup,
+                &self.p,
+                self.dangling_sum,
+                i,
+            );
+            self.sum += self.new_p[i];
+        }
+    }
+
+    fn converge_nodes(&mut self, nodes: std::ops::Range<usize>) {
+        for i in nodes {
+            self.p[i] /= self.sum;
+            self.next_change += (self.p[i] - self.new_p[i]).abs();
+            if self.setup.inverse_out_links[i] == 0.0 {
+                self.next_dangling_sum += self.p[i];
+            }
+        }
+    }
+
+    fn collect_nodes(&mut self, nodes: std::ops::Range<usize>) {
+        for i in nodes {
+            if let Some(score) = self.config.emitted_score(self.p[i]) {
+                self.ranked.push((self.graph.key_of(i), score));
+            }
+        }
+    }
+
+    /// Starts sorting the pairs from start to end, by score or by the tie-break, and returns the
+    /// sort stage.
+    fn begin_sort(&mut self, start: usize, end: usize, by_tie_order: bool) -> Stage {
+        self.sort_start = start;
+        self.sort_end = end;
+        self.by_tie_order = by_tie_order;
+        Stage::Sort
+    }
+
+    /// Returns the stage following the sort of the sorted range.
+    fn sorted(&mut self) -> Stage {
+        if self.by_tie_order {
+            // Resume looking for runs of ties after the sorted run.
+            self.next_index = self.tie_start;
+            Stage::Ties
+        } else {
+            self.ties_stage()
+        }
+    }
+
+    /// Returns the stage following a merge pass of runs of width pairs.
+    fn merge_pass_done(&mut self, width: usize) -> Stage {
+        self.merged.clear();
+        match width.checked_mul(2) {
+            Some(width) if width < self.sort_end - self.sort_start => Stage::Merge { width },
+            _ => self.sorted(),
+        }
+    }
+
+    /// Returns the order of the sort in progress.
+    fn sort_order(&self) -> impl Fn(&(usize, f64), &(usize, f64)) -> Ordering {
+        let tie_break = self.config.tie_break;
+        let by_tie_order = self.by_tie_order;
+        move |a, b| {
+            if by_tie_order {
+                tie_break.tie_order(a, b)
+            } else {
+                tie_break.compare(a, b)
+            }
+        }
+    }
+
+    fn sort_runs(&mut self, runs: std::ops::Range<usize>) {
+        let order = self.sort_order();
+        for run in runs {
+            let start = self.sort_start + run * self.chunk_size;
+            let end = (start + self.chunk_size).min(self.sort_end);
+            self.ranked[start..end].sort_unstable_by(&order);
+        }
+    }
+
+    /// Merges the pairs of runs of width pairs of the sorted range into merged, producing the
+    /// pairs at the offsets of outputs.
+    fn merge_pairs(&mut self, width: usize, outputs: std::ops::Range<usize>) {
+        let order = self.sort_order();
+        for output in outputs {
+            let start = self.sort_start + output - output % (2 * width);
+            let middle = (start + width).min(self.sort_end);
+            let end = (middle + width).min(self.sort_end);
+            if self.sort_start + output == start {
+                self.left = start;
+                self.right = middle;
+            }
+
+            let take_left = self.right == end
+                || (self.left < middle
+                    && order(&self.ranked[self.left], &self.ranked[self.right])
+                        != Ordering::Greater);
+            if take_left {
+                self.merged.push(self.ranked[self.left]);
+                self.left += 1;
+            } else {
+                self.merged.push(self.ranked[self.right]);
+                self.right += 1;
+            }
+        }
+    }
+
+    fn ties_stage(&mut self) -> Stage {
+        self.tie_start = 0;
+        match self.config.tie_break {
+            TieBreak::Unordered => Stage::Done,
+            _ => Stage::Ties,
+        }
+    }
+
+    fn copy_back(&mut self, offsets: std::ops::Range<usize>) {
+        let start = self.sort_start;
+        self.ranked[start + offsets.start..start + offsets.end]
+            .copy_from_slice(&self.merged[offsets]);
+    }
+
+    /// Looks for the end of the run of ties starting at tie_start among positions, returning it
+    /// if the run holds more than one pair and needs to be sorted.
+    fn find_tie_run(&mut self, positions: std::ops::Range<usize>) -> Option<usize> {
+        for position in positions {
+            if !TieBreak::is_tie(self.ranked[self.tie_start].1, self.ranked[position].1) {
+                if position - self.tie_start > 1 {
+                    return Some(position);
+                }
+                self.tie_start = position;
+            }
+        }
+        None
+    }
+
+    /// Runs the remaining chunks without yielding and returns the scores sorted by descending
+    /// score.
+    pub fn into_ranked(mut self) -> Vec<(usize, f64)> {
+        while !self.process_chunk() {}
+        self.ranked
+    }
+}
+
+impl Pagerank {
+    /// Starts a PageRank computation that is advanced in chunks of at most chunk_size nodes.
+    ///
+    /// The computation never uses the rayon thread pool, so it also runs where no threads can be
+    /// spawned.
+    ///
+    /// # Arguments
+    ///
+    /// * config - The parameters of the computation. The solver and the threads are ignored,
+    ///   the chunked mode always runs power iteration on the calling thread.
+    /// * chunk_size - The maximum number of nodes processed per call to process_chunk.
+    ///
+    /// # Examples
+    ///
+    /// let mut ranking = pagerank.rank_chunked(&RankConfig::default(), 10_000);
+    /// while !ranking.process_chunk() {
+    ///     // Let the UI thread handle pending events.
+    /// }
+    /// let result = ranking.into_ranked();
+    ///
+    pub fn rank_chunked(&self, config: &RankConfig, chunk_size: usize) -> ChunkedRank<'_> {
+        ChunkedRank::new(self, config, chunk_size)
+    }
+
+    /// Computes the PageRank scores in chunks, awaiting the future returned by yield_now between
+    /// chunks.
+    ///
+    /// No executor is assumed: yield_now can return any future that hands control back to the
+    /// environment, e.g. one resolving on the next tick of a browser's event loop.
+    ///
+    /// # Arguments
+    ///
+    /// * config - The parameters of the computation.
+    /// * chunk_size - The maximum number of nodes processed between two yields.
+    /// * yield_now - Called between chunks, the computation resumes when its future completes.
+    ///
+    /// # Examples
+    ///
+    /// let result = pagerank
+    ///     .rank_chunked_async(&RankConfig::default(), 10_000, || next_tick())
+    ///     .await;
+    ///
+    pub async fn rank_chunked_async<Y, F>(
+        &self,
+        config: &RankConfig,
+        chunk_size: usize,
+        mut yield_now: Y,
+    ) -> Vec<(usize, f64)>
+    where
+        Y: FnMut() -> F,
+        F: Future<Output = ()>,
+    {
+        let mut ranking = self.rank_chunked(config, chunk_size);
+        while !ranking.process_chunk() {
+            yield_now().await;
+        }
+        ranking.into_ranked()
+    }
+}
@@ -0,0 +1,281 @@
+//! This module provides the RankConfig struct, which gathers the parameters of a PageRank
+//! computation so that optional rank-time behaviour can be selected without changing the graph.
+use crate::sharding::mix64;
+use rayon::prelude::*;
+use std::cmp::Ordering;
+use std::collections::HashSet;
+
+/// Parameters of a PageRank computation.
+///
+/// RankConfig::new covers the common case of only choosing the damping factor and tolerance,
+/// the remaining fields can be adjusted afterwards.
+///
+/// Fields:
+/// - following_prob: The probability of following a link (damping factor).
+/// - tolerance: The convergence tolerance; computation stops when the change in scores falls
+///   below this threshold.
+/// - no_teleport: Keys of nodes that never receive teleport mass, neither from random jumps nor
+///   from the redistribution of dangling nodes. They can still receive score through links.
+/// - no_emit: Keys of nodes whose score is not propagated through their outgoing links (e.g.
+///   known bots). Their score is redistributed like the score of a dangling node instead.
+/// - initial: The scores the iterations start from.
+/// - numa_aware: Whether every thread of the pool computes the scores of the same contiguous
+///   range of nodes in all iterations, after being the first to touch that range of the score
+///   buffers. On multi-socket machines this keeps the pages of each range on the memory of the
+///   socket running its thread, as long as the threads themselves are pinned (e.g. with numactl
+///   or a start handler of a custom pool). Ignored by rank_reversed.
+/// - threads: The number of threads used by the computation, Some(1) runs it sequentially.
+///   None, like Some(0), uses the global rayon pool. Otherwise the computation runs on a
+///   dedicated pool, built on first use and reused by every later computation with the same
+///   thread count, so an interactive re-rank can use a few threads while a nightly full run uses
+///   all cores. Ignored by chunked and sharded computations.
+/// - score_floor: The minimum score emitted, lower scores are raised to it (e.g. a minimum
+///   display score). The scores are bounded after convergence, so they no longer sum to 1.
+/// - score_ceiling: The maximum score emitted, higher scores are lowered to it. Takes precedence
+///   over score_floor if it is lower.
+/// - tie_break: How nodes with identical scores are ordered in the result.
+/// - drop_below: The converged score below which nodes are left out of the result, to shrink
+///   the results of huge graphs where most nodes have a negligible score. Applied before
+///   score_floor and score_ceiling. Pagerank::rank_pruned reports the dropped score mass.
+/// - solver: The algorithm computing the scores, Solver::Auto picks one from the properties of
+///   the graph. Ignored by chunked and sharded computations, which use power iteration.
+///
+/// Keys that are not part of the graph are ignored. The nodes stay in the graph and are still
+/// ranked, the filters only apply to the computation they are passed to.
+#[derive(Debug, Clone)]
+pub struct RankConfig {
+    pub following_prob: f64,
+    pub tolerance: f64,
+    pub no_teleport: HashSet<usize>,
+    pub no_emit: HashSet<usize>,
+    pub initial: InitialDistribution,
+    pub numa_aware: bool,
+    pub threads: Option<usize>,
+    pub score_floor: Option<f64>,
+    pub score_ceiling: Option<f64>,
+    pub tie_break: TieBreak,
+    pub drop_below: Option<f64>,
+    pub solver: Solver,
+}
+
+/// The scores a PageRank computation starts iterating from.
+///
+/// The initialization doesn't change the converged scores, but starting closer to them can
+/// noticeably cut the number of iterations, especially on skewed graphs.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub enum InitialDistribution {
+    /// Every node starts with the same score.
+    #[default]
+    Uniform,
+    /// Starts from a previous result, typically of the same graph before some links changed.
+    /// Nodes missing from the result start with the uniform score.
+    WarmStart(Vec<(usize, f64)>),
+    /// Scores proportional to the number of incoming links of each node plus one.
+    DegreeProportional,
+    /// Scores proportional to the teleport distribution, i.e. uniform over the nodes that are
+    /// not excluded with no_teleport.
+    TeleportProportional,
+    /// All the score starts on a single node, as in local personalized PageRank from that
+    /// source. Falls back to Uniform if the node is not part of the graph.
+    Source(usize),
+}
+
+/// The relative difference below which two scores are tied for TieBreak::Key and
+/// TieBreak::SeededHash.
+///
+/// The scores of nodes that are symmetric in the graph can differ in their last bits, because
+/// the scores of their incoming links are added in a different order. After sorting by
+/// descending score, every run of scores within TIE_EPSILON times the first score of the run is
+/// ordered by the tie-break.
+pub const TIE_EPSILON: f64 = 1e-9;
+
+/// The order of nodes with identical scores in a rank result.
+///
+/// Identical scores are common in symmetric structures, where an unordered sort makes the
+/// output and any top-k selection vary between runs and platforms. Key and SeededHash consider
+/// scores within a relative difference of TIE_EPSILON identical.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum TieBreak {
+    /// Ties are returned in an unspecified order, which is the fastest option.
+    #[default]
+    Unordered,
+    /// Ties are sorted by ascending key.
+    Key,
+    /// Ties are sorted by a hash of their key seeded with the given value, which is stable across
+    /// runs and platforms but doesn't favour low keys.
+    SeededHash(u64),
+}
+
+/// The largest number of nodes Solver::Exact solves by elimination, which bounds the matrix to
+/// 800 MB. Larger graphs are ranked with power iteration, as allocating the matrix can succeed
+/// on systems that overcommit memory and only fail once it is written.
+pub const EXACT_MAX_NODES: usize = 10_000;
+
+/// The algorithm computing the scores of a PageRank computation.
+///
+/// All solvers converge to the same scores. The alternatives to power iteration solve the linear
+/// system x = following_prob * M * x + (1 - following_prob) * teleport, where M holds the share
+/// of score sent through each link, whose normalized solution are the PageRank scores. They fall
+/// back to power iteration when following_prob is 1 or more and in rank_reversed. rank_for and
+/// rank_budgeted stop PowerIteration and GaussSeidel between iterations, Condensation and Exact
+/// run to completion once started.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Solver {
+    /// Updates all scores from the previous iteration, in parallel over the nodes.
+    #[default]
+    PowerIteration,
+    /// Updates the scores in place, so each update already uses the updated scores of earlier
+    /// nodes. It typically needs about half the iterations of power iteration but runs on a
+    /// single thread.
+    GaussSeidel,
+    /// Solves the strongly connected components of the graph one after the other in topological
+    /// order with Gauss-Seidel, so only the nodes of components with cycles are visited more than
+    /// once. Acyclic graphs are solved exactly in a single pass.
+    Condensation,
+    /// Solves the linear system with Gaussian elimination, which needs memory quadratic and time
+    /// cubic in the number of nodes. Falls back to power iteration for graphs of more than
+    /// EXACT_MAX_NODES nodes and if the matrix can't be allocated.
+    Exact,
+    /// Picks Exact for small graphs where elimination is estimated to be cheaper than iterating,
+    /// which depends on their number of nodes and links and on the tolerance, Condensation when
+    /// no strongly connected component holds more than half of the nodes (including acyclic
+    /// graphs), GaussSeidel when the computation runs on at most two threads and PowerIteration
+    /// otherwise. PowerIteration is always picked when numa_aware is set.
+    Auto,
+}
+
+impl TieBreak {
+    /// Sorts ranked by descending score, ordering ties according to self.
+    pub(crate) fn sort(self, ranked: &mut [(usize, f64)]) {
+        match self {
+            TieBreak::Unordered => ranked.par_sort_unstable_by(Self::by_score),
+            _ => {
+                ranked.par_sort_unstable_by(|a, b| self.compare(a, b));
+                self.order_ties(ranked);
+            }
+        }
+    }
+
+    /// Returns Less if a is ranked before b, comparing the exact scores. Unordered ties are
+    /// ordered like Key, so the order is total. Sorting with compare and then calling
+    /// order_ties gives the order of sort.
+    pub(crate) fn compare(self, a: &(usize, f64), b: &(usize, f64)) -> Ordering {
+        Self::by_score(a, b).then_with(|| self.tie_order(a, b))
+    }
+
+    /// Returns Less if a is ranked before b when their scores are tied.
+    pub(crate) fn tie_order(self, a: &(usize, f64), b: &(usize, f64)) -> Ordering {
+        match self {
+            TieBreak::Unordered | TieBreak::Key => a.0.cmp(&b.0),
+            TieBreak::SeededHash(seed) => {
+                let seed = mix64(seed);
+                let hash = |key: usize| mix64(key as u64 ^ seed);
+                hash(a.0).cmp(&hash(b.0)).then(a.0.cmp(&b.0))
+            }
+        }
+    }
+
+    /// Returns whether score is tied with first, the higher score starting a run of ties.
+    pub(crate) fn is_tie(first: f64, score: f64) -> bool {
+        first - score <= TIE_EPSILON * first.abs()
+    }
+
+    /// Returns the end of the run of ties starting at start in ranked, sorted by descending
+    /// score.
+    pub(crate) fn tie_run_end(ranked: &[(usize, f64)], start: usize) -> usize {
+        let first = ranked[start].1;
+        start
+            + 1
+            + ranked[start + 1..]
+                .iter()
+                .take_while(|&&(_, score)| Self::is_tie(first, score))
+                .count()
+    }
+
+    /// Orders the runs of ties of ranked, sorted with compare, by the tie-break.
+    pub(crate) fn order_ties(self, ranked: &mut [(usize, f64)]) {
+        if self == TieBreak::Unordered {
+            return;
+        }
+
+        let mut start = 0;
+        while start < ranked.len() {
+            let end = Self::tie_run_end(ranked, start);
+            ranked[start..end].sort_unstable_by(|a, b| self.tie_order(a, b));
+            start = end;
+        }
+    }
+
+    fn by_score(a: &(usize, f64), b: &(usize, f64)) -> Ordering {
+        b.1.partial_cmp(&a.1).unwrap_or(Ordering::Equal)
+    }
+}
+
+impl RankConfig {
+    /// Constructs a RankConfig with the given damping factor and tolerance and no filters.
+    ///
+    /// # Examples
+    ///
+    /// let mut config = RankConfig::new(0.85, 1e-6);
+    /// config.no_emit.insert(42); // Links from node 42 don't transfer any score.
+    ///
+    pub fn new(following_prob: f64, tolerance: f64) -> RankConfig {
+        RankConfig {
+            following_prob,
+            tolerance,
+            no_teleport: HashSet::new(),
+            no_emit: HashSet::new(),
+            initial: InitialDistribution::Uniform,
+            numa_aware: false,
+            threads: None,
+            score_floor: None,
+            score_ceiling: None,
+            tie_break: TieBreak::Unordered,
+            drop_below: None,
+            solver: Solver::PowerIteration,
+        }
+    }
+
+    /// Turns the converged (key, score) pairs into a result: drops the scores below drop_below,
+    /// applies score_floor and score_ceiling and sorts by descending score.
+    ///
+    /// Returns the number of dropped nodes and the sum of their scores.
+    pub(crate) fn emit(&self, ranked: &mut Vec<(usize, f64)>) -> (usize, f64) {
+        let mut dropped = 0;
+        let mut dropped_mass = 0.0;
+        ranked.retain_mut(|(_, score)| match self.emitted_score(*score) {
+            Some(emitted) => {
+                *score = emitted;
+                true
+            }
+            None => {
+                dropped += 1;
+                dropped_mass += *score;
+                false
+            }
+        });
+
+        self.tie_break.sort(ranked);
+        (dropped, dropped_mass)
+    }
+
+    /// Returns the score emitted for a converged score after applying score_floor and
+    /// score_ceiling, or None if drop_below leaves it out of the result.
+    pub(crate) fn emitted_score(&self, score: f64) -> Option<f64> {
+        if self.drop_below.is_some_and(|threshold| score < threshold) {
+            return None;
+        }
+
+        let score = self.score_floor.map_or(score, |floor| score.max(floor));
+        Some(
+            self.score_ceiling
+                .map_or(score, |ceiling| score.min(ceiling)),
+        )
+    }
+}
+
+impl Default for RankConfig {
+    fn default() -> Self {
+        RankConfig::new(0.85, 0.0001)
+    }
+}
@@ -4,12 +4,26 @@ use std::fmt::{self, Display, Formatter};
 #[derive(Debug)]
 pub enum PagerankError {
     CapacityError(String),
+    LinkNotFoundError(String),
+    ShardError(String),
+    ParseError(String),
+    IoError(String),
+    AllocationError(String),
+    SnapshotError(String),
+    ProvenanceError(String),
 }
 
 impl Display for PagerankError {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
         match self {
             PagerankError::CapacityError(msg) => write!(f, "{}", msg),
+            PagerankError::LinkNotFoundError(msg) => write!(f, "{}", msg),
+            PagerankError::ShardError(msg) => write!(f, "{}", msg),
+            PagerankError::ParseError(msg) => write!(f, "{}", msg),
+            PagerankError::IoError(msg) => write!(f, "{}", msg),
+            PagerankError::AllocationError(msg) => write!(f, "{}", msg),
+            PagerankError::SnapshotError(msg) => write!(f, "{}", msg),
+            PagerankError::ProvenanceError(msg) => write!(f, "{}", msg),
         }
     }
 }
@@ -0,0 +1,123 @@
+//! This module provides writers producing edge lists in the format read by the loaders, with
+//! optional remapping of the node keys so production graphs can be shared for debugging
+//! without leaking their identifiers.
+use crate::errors::PagerankError;
+use crate::pagerank::DEFAULT_EDGE_TYPE;
+use crate::sharding::mix64;
+use crate::Pagerank;
+use std::collections::HashMap;
+use std::io::Write;
+
+impl Pagerank {
+    /// Writes every link of the graph as an edge list line, returning the number of links.
+    ///
+    /// Links of the default type are written as "from to", other links as "from to type".
+    /// Nodes without any link are not part of the edge list.
+    ///
+    /// # Arguments
+    ///
+    /// * writer - Where the edge list is written, e.g. a BufWriter over a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns a PagerankError if writing fails.
+    ///
+    /// # Examples
+    ///
+    /// let written = pagerank.write_edge_list(BufWriter::new(File::create("edges.txt")?))?;
+    ///
+    pub fn write_edge_list<W: Write>(&self, writer: W) -> Result<usize, PagerankError> {
+        self.write_edge_list_remapped(writer, |key| key)
+    }
+
+    /// Writes every link of the graph as an edge list line like write_edge_list, replacing each
+    /// node key with remap(key).
+    ///
+    /// # Arguments
+    ///
+    /// * writer - Where the edge list is written.
+    /// * remap - Returns the key written in place of a node key, e.g. a keyed hash.
+    ///
+    /// # Errors
+    ///
+    /// Returns a PagerankError if writing fails.
+    ///
+    /// # Examples
+    ///
+    /// pagerank.write_edge_list_remapped(writer, |key| pseudonyms[&key])?;
+    ///
+    pub fn write_edge_list_remapped<W, F>(
+        &self,
+        mut writer: W,
+        mut remap: F,
+    ) -> Result<usize, PagerankError>
+    where
+        W: Write,
+        F: FnMut(usize) -> usize,
+    {
+        let io_error = |e: std::io::Error| PagerankError::IoError(e.to_string());
+        let remapped: Vec<usize> = (0..self.size())
+            .map(|index| remap(self.key_of(index)))
+            .collect();
+
+        let mut written = 0;
+        for (to_as_index, &to) in remapped.iter().enumerate() {
+            let types = self.in_link_types_of(to_as_index);
+            for (&from_as_index, &edge_type) in self.in_links_of(to_as_index).iter().zip(types) {
+                let from = remapped[from_as_index];
+                if edge_type == DEFAULT_EDGE_TYPE {
+                    writeln!(writer, "{} {}", from, to).map_err(io_error)?;
+                } else {
+                    writeln!(writer, "{} {} {}", from, to, edge_type).map_err(io_error)?;
+                }
+                written += 1;
+            }
+        }
+
+        writer.flush().map_err(io_error)?;
+        Ok(written)
+    }
+
+    /// Writes every link of the graph as an edge list line like write_edge_list, replacing the
+    /// node keys with a random permutation of 0..n, where n is the number of nodes.
+    ///
+    /// The permutation only depends on seed and the graph. It is returned as a map from the
+    /// original keys to the written keys, so results computed on the shared graph can be
+    /// translated back.
+    ///
+    /// # Arguments
+    ///
+    /// * writer - Where the edge list is written.
+    /// * seed - The seed of the permutation.
+    ///
+    /// # Errors
+    ///
+    /// Returns a PagerankError if writing fails.
+    ///
+    /// # Examples
+    ///
+    /// let mapping = pagerank.write_edge_list_anonymized(writer, rand::random())?;
+    ///
+    pub fn write_edge_list_anonymized<W: Write>(
+        &self,
+        writer: W,
+        seed: u64,
+    ) -> Result<HashMap<usize, usize>, PagerankError> {
+        let mut permutation: Vec<usize> = (0..self.size()).collect();
+        // Fisher-Yates shuffle driven by a SplitMix64 sequence.
+        let mut state = seed;
+        for i in (1..permutation.len()).rev() {
+            state = state.wrapping_add(0x9e3779b97f4a7c15);
+            let j = (mix64(state) % (i as u64 + 1)) as usize;
+            permutation.swap(i, j);
+        }
+
+        let mapping: HashMap<usize, usize> = permutation
+            .into_iter()
+            .enumerate()
+            .map(|(index, anonymized)| (self.key_of(index), anonymized))
+            .collect();
+        self.write_edge_list_remapped(writer, |key| mapping[&key])?;
+        Ok(mapping)
+    }
+}
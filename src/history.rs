@@ -0,0 +1,144 @@
+//! This module provides the ScoreHistory struct, which retains the last rank results to answer
+//! per-node trend queries, so "trending nodes" features can be built without an external time
+//! series store.
+use std::collections::{HashMap, VecDeque};
+
+/// The direction in which a node's score moves over the retained results.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Trend {
+    Rising,
+    Falling,
+    Flat,
+}
+
+/// A ring buffer of the last rank results.
+///
+/// Trends are computed over the retained results as a least squares fit of the score against
+/// the position of the result, so the slope is the average score change per recorded result.
+///
+/// Fields:
+/// - capacity: The maximum number of results retained, the oldest is dropped when exceeded.
+/// - results: The retained results from oldest to newest, as scores by node identifier.
+#[derive(Debug, Clone)]
+pub struct ScoreHistory {
+    capacity: usize,
+    results: VecDeque<HashMap<usize, f64>>,
+}
+
+impl ScoreHistory {
+    /// Constructs an empty history retaining up to capacity results.
+    ///
+    /// # Examples
+    ///
+    /// let mut history = ScoreHistory::new(24); // A day of hourly results.
+    ///
+    pub fn new(capacity: usize) -> ScoreHistory {
+        ScoreHistory {
+            capacity,
+            results: VecDeque::with_capacity(capacity),
+        }
+    }
+
+    /// Records a rank result, dropping the oldest retained result if the history is full.
+    ///
+    /// # Arguments
+    ///
+    /// * ranked - The result of a rank computation.
+    ///
+    /// # Examples
+    ///
+    /// history.record(&pagerank.rank(0.85, 1e-6));
+    ///
+    pub fn record(&mut self, ranked: &[(usize, f64)]) {
+        if self.capacity == 0 {
+            return;
+        }
+        if self.results.len() == self.capacity {
+            self.results.pop_front();
+        }
+        self.results.push_back(ranked.iter().copied().collect());
+    }
+
+    /// Returns the number of retained results.
+    pub fn len(&self) -> usize {
+        self.results.len()
+    }
+
+    /// Returns whether no result is retained.
+    pub fn is_empty(&self) -> bool {
+        self.results.is_empty()
+    }
+
+    /// Returns the scores of key in the retained results, from oldest to newest. Results where
+    /// the node is missing count as a score of 0.
+    pub fn scores_of(&self, key: usize) -> Vec<f64> {
+        self.results
+            .iter()
+            .map(|result| result.get(&key).copied().unwrap_or(0.0))
+            .collect()
+    }
+
+    /// Returns the average change of the score of key per recorded result, or None if fewer
+    /// than two results are retained.
+    pub fn slope(&self, key: usize) -> Option<f64> {
+        let scores = self.scores_of(key);
+        if scores.len() < 2 {
+            return None;
+        }
+
+        let n = scores.len() as f64;
+        let mean_x = (n - 1.0) / 2.0;
+        let mean_y = scores.iter().sum::<f64>() / n;
+        let (covariance, variance) =
+            scores
+                .iter()
+                .enumerate()
+                .fold((0.0, 0.0), |(covariance, variance), (x, &y)| {
+                    let dx = x as f64 - mean_x;
+                    (covariance + dx * (y - mean_y), variance + dx * dx)
+                });
+        Some(covariance / variance)
+    }
+
+    /// Returns the trend of the score of key, or None if fewer than two results are retained.
+    ///
+    /// # Arguments
+    ///
+    /// * key - The node identifier.
+    /// * threshold - The absolute slope up to which the score is considered flat.
+    pub fn trend(&self, key: usize, threshold: f64) -> Option<Trend> {
+        self.slope(key).map(|slope| {
+            if slope > threshold {
+                Trend::Rising
+            } else if slope < -threshold {
+                Trend::Falling
+            } else {
+                Trend::Flat
+            }
+        })
+    }
+
+    /// Returns up to n nodes of the latest result with the steepest rising scores, as
+    /// (key, slope) pairs sorted by descending slope.
+    ///
+    /// # Examples
+    ///
+    /// for (key, slope) in history.trending(10) {
+    ///     println!("Node {} gains {} per result", key, slope);
+    /// }
+    ///
+    pub fn trending(&self, n: usize) -> Vec<(usize, f64)> {
+        let Some(latest) = self.results.back() else {
+            return Vec::new();
+        };
+
+        let mut trending: Vec<(usize, f64)> = latest
+            .keys()
+            .filter_map(|&key| Some((key, self.slope(key)?)))
+            .filter(|&(_, slope)| slope > 0.0)
+            .collect();
+        trending.sort_unstable_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+        trending.truncate(n);
+        trending
+    }
+}
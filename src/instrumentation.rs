@@ -0,0 +1,34 @@
+//! This module emits metrics about rank computations through the `metrics` facade, so operators
+//! can dashboard the ranking subsystem with any `metrics` recorder (Prometheus, StatsD, ...).
+//!
+//! The metrics are recorded once per computation by rank, rank_with_config, rank_filtered,
+//! rank_reversed and the budgeted rank methods. Chunked and sharded computations are not
+//! recorded.
+use crate::Pagerank;
+use std::time::Duration;
+
+/// Counter of the iterations run by all the computations.
+pub const ITERATIONS: &str = "pagerank_iterations_total";
+/// Counter of the node and link visits of all the computations.
+pub const EDGE_VISITS: &str = "pagerank_edge_visits_total";
+/// Gauge of the node and link visits per second of the last computation.
+pub const EDGE_VISITS_PER_SECOND: &str = "pagerank_edge_visits_per_second";
+/// Gauge of the change in scores of the last iteration of the last computation.
+pub const RESIDUAL: &str = "pagerank_residual";
+/// Gauge of the estimated heap memory used by the last ranked graph, in bytes.
+///
+/// The estimate walks every node slot up to the capacity of the graph once per computation,
+/// which costs about as much as one iteration over a graph filled to its capacity.
+pub const MEMORY_BYTES: &str = "pagerank_memory_bytes";
+
+pub(crate) fn record_rank(graph: &Pagerank, iterations: usize, residual: f64, elapsed: Duration) {
+    let edge_visits = iterations * graph.visits_per_iteration();
+
+    metrics::counter!(ITERATIONS).increment(iterations as u64);
+    metrics::counter!(EDGE_VISITS).increment(edge_visits as u64);
+    if !elapsed.is_zero() {
+        metrics::gauge!(EDGE_VISITS_PER_SECOND).set(edge_visits as f64 / elapsed.as_secs_f64());
+    }
+    metrics::gauge!(RESIDUAL).set(residual);
+    metrics::gauge!(MEMORY_BYTES).set(graph.memory_bytes() as f64);
+}
@@ -1,6 +1,43 @@
 #![doc = include_str!("../README.md")]
 
+#[cfg(feature = "proptest")]
+pub mod arbitrary;
+#[cfg(feature = "bench_utils")]
+pub mod bench_utils;
+mod budget;
+mod chunked;
+mod config;
 pub mod errors;
+mod explain;
+mod export;
+mod history;
+#[cfg(feature = "metrics")]
+pub mod instrumentation;
+mod loader;
+mod namespaces;
 mod pagerank;
+mod persistence;
+mod pipeline;
+mod publisher;
+mod sharding;
+mod solver;
+mod storage;
+mod subgraph;
+mod top_k;
 
-pub use pagerank::Pagerank;
+pub use budget::BudgetedRank;
+pub use chunked::ChunkedRank;
+pub use config::{InitialDistribution, RankConfig, Solver, TieBreak, EXACT_MAX_NODES, TIE_EPSILON};
+pub use history::{ScoreHistory, Trend};
+pub use loader::{LoadError, LoadReport, MAX_REPORTED_ERRORS};
+pub use namespaces::PagerankNamespaces;
+pub use pagerank::{
+    EdgeType, NodeOrder, Pagerank, PagerankSnapshot, Provenance, PrunedRank, DEFAULT_EDGE_TYPE,
+    NO_PROVENANCE,
+};
+pub use persistence::{ScoreSnapshot, SNAPSHOT_VERSION};
+pub use pipeline::PagerankPipeline;
+pub use publisher::{ScoreDelta, ScorePublisher};
+pub use sharding::{shard_of, PagerankShard, ShardExchange};
+pub use subgraph::RankedSubgraph;
+pub use top_k::TopKRank;
@@ -0,0 +1,267 @@
+//! This module provides loaders adding the links of an edge list to a Pagerank graph.
+//!
+//! An edge list is a text file with one link per line: the key of the source node and the key
+//! of the target node, optionally followed by the type of the link, separated by whitespace or
+//! commas. Empty lines and lines starting with '#' or '%' are ignored, which covers the format of
+//! most public graph datasets.
+//!
+//! The file loaders transparently decompress gzip and zstd compressed edge lists when the `gzip`
+//! and `zstd` features are enabled, so large datasets don't need to be decompressed to disk.
+use crate::errors::PagerankError;
+use crate::pagerank::{EdgeType, DEFAULT_EDGE_TYPE};
+use crate::Pagerank;
+use std::fs::File;
+use std::io::{BufRead, BufReader, ErrorKind};
+use std::path::Path;
+
+/// The maximum number of errors kept in a LoadReport, further errors are only counted.
+pub const MAX_REPORTED_ERRORS: usize = 100;
+
+/// A record that couldn't be loaded by a lenient loader.
+///
+/// Fields:
+/// - line: The number of the line of the record, starting at 1.
+/// - error: Why the record was skipped.
+#[derive(Debug)]
+pub struct LoadError {
+    pub line: usize,
+    pub error: PagerankError,
+}
+
+/// The outcome of a lenient load.
+///
+/// Fields:
+/// - loaded: The number of links added to the graph.
+/// - skipped: The number of records that were skipped because they were malformed or didn't fit
+///   within the capacity of the graph.
+/// - errors: The first MAX_REPORTED_ERRORS errors, in the order they were found.
+#[derive(Debug, Default)]
+pub struct LoadReport {
+    pub loaded: usize,
+    pub skipped: usize,
+    pub errors: Vec<LoadError>,
+}
+
+impl LoadReport {
+    fn skip(&mut self, line: usize, error: PagerankError) {
+        self.skipped += 1;
+        if self.errors.len() < MAX_REPORTED_ERRORS {
+            self.errors.push(LoadError { line, error });
+        }
+    }
+}
+
+enum Record {
+    Link(usize, usize, EdgeType),
+    Ignored,
+}
+
+fn parse_record(line: &[u8]) -> Result<Record, PagerankError> {
+    let line = std::str::from_utf8(line)
+        .map_err(|e| PagerankError::ParseError(format!("Invalid UTF-8: {}", e)))?
+        .trim();
+    if line.is_empty() || line.starts_with('#') || line.starts_with('%') {
+        return Ok(Record::Ignored);
+    }
+
+    let fields: Vec<&str> = line
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|field| !field.is_empty())
+        .collect();
+    let parse_error = || {
+        let message = format!("Expected 'from to [type]' but found '{}'", line);
+        PagerankError::ParseError(message)
+    };
+
+    match fields[..] {
+        [from, to] => Ok(Record::Link(
+            from.parse().map_err(|_| parse_error())?,
+            to.parse().map_err(|_| parse_error())?,
+            DEFAULT_EDGE_TYPE,
+        )),
+        [from, to, edge_type] => Ok(Record::Link(
+            from.parse().map_err(|_| parse_error())?,
+            to.parse().map_err(|_| parse_error())?,
+            edge_type.parse().map_err(|_| parse_error())?,
+        )),
+        _ => Err(parse_error()),
+    }
+}
+
+/// Calls on_record with the line number and the parsed record of every line of reader.
+///
+/// Stops at the first error returned by on_record and on read errors.
+fn for_each_record<R, F>(mut reader: R, mut on_record: F) -> Result<(), PagerankError>
+where
+    R: BufRead,
+    F: FnMut(usize, Result<Record, PagerankError>) -> Result<(), PagerankError>,
+{
+    let mut buffer = Vec::new();
+    let mut line = 0;
+    loop {
+        buffer.clear();
+        match reader.read_until(b'\n', &mut buffer) {
+            Ok(0) => return Ok(()),
+            Ok(_) => {
+                line += 1;
+                on_record(line, parse_record(&buffer))?;
+            }
+            Err(e) if e.kind() == ErrorKind::Interrupted => continue,
+            Err(e) => return Err(PagerankError::IoError(e.to_string())),
+        }
+    }
+}
+
+const GZIP_MAGIC: [u8; 2] = [0x1f, 0x8b];
+const ZSTD_MAGIC: [u8; 4] = [0x28, 0xb5, 0x2f, 0xfd];
+
+#[cfg(feature = "gzip")]
+fn gzip_reader(reader: BufReader<File>) -> Result<Box<dyn BufRead>, PagerankError> {
+    let decoder = flate2::bufread::MultiGzDecoder::new(reader);
+    Ok(Box::new(BufReader::new(decoder)))
+}
+
+#[cfg(not(feature = "gzip"))]
+fn gzip_reader(_reader: BufReader<File>) -> Result<Box<dyn BufRead>, PagerankError> {
+    let message = "Reading gzip compressed edge lists requires the gzip feature".to_string();
+    Err(PagerankError::IoError(message))
+}
+
+#[cfg(feature = "zstd")]
+fn zstd_reader(reader: BufReader<File>) -> Result<Box<dyn BufRead>, PagerankError> {
+    let decoder = zstd::stream::read::Decoder::with_buffer(reader)
+        .map_err(|e| PagerankError::IoError(e.to_string()))?;
+    Ok(Box::new(BufReader::new(decoder)))
+}
+
+#[cfg(not(feature = "zstd"))]
+fn zstd_reader(_reader: BufReader<File>) -> Result<Box<dyn BufRead>, PagerankError> {
+    let message = "Reading zstd compressed edge lists requires the zstd feature".to_string();
+    Err(PagerankError::IoError(message))
+}
+
+/// Opens an edge list file, decompressing it if it starts with the gzip or zstd magic bytes.
+pub(crate) fn open_edge_list(path: &Path) -> Result<Box<dyn BufRead>, PagerankError> {
+    let io_error = |e: std::io::Error| PagerankError::IoError(format!("{}: {}", path.display(), e));
+    let mut reader = BufReader::new(File::open(path).map_err(io_error)?);
+
+    let magic = reader.fill_buf().map_err(io_error)?;
+    if magic.starts_with(&GZIP_MAGIC) {
+        gzip_reader(reader)
+    } else if magic.starts_with(&ZSTD_MAGIC) {
+        zstd_reader(reader)
+    } else {
+        Ok(Box::new(reader))
+    }
+}
+
+impl Pagerank {
+    /// Adds the links of an edge list file to the graph, failing on the first bad record.
+    ///
+    /// Compressed files are detected by their content, not their extension.
+    ///
+    /// # Arguments
+    ///
+    /// * path - The path of the edge list, plain or compressed with gzip or zstd.
+    ///
+    /// # Errors
+    ///
+    /// Returns a PagerankError if the file can't be opened, is compressed with a format whose
+    /// feature is not enabled, or if load_edge_list fails.
+    ///
+    /// # Examples
+    ///
+    /// let loaded = pagerank.load_edge_list_file("soc-LiveJournal1.txt.gz")?;
+    ///
+    pub fn load_edge_list_file<P: AsRef<Path>>(&mut self, path: P) -> Result<usize, PagerankError> {
+        self.load_edge_list(open_edge_list(path.as_ref())?)
+    }
+
+    /// Adds the links of an edge list file to the graph, skipping bad records like
+    /// load_edge_list_lenient.
+    ///
+    /// # Arguments
+    ///
+    /// * path - The path of the edge list, plain or compressed with gzip or zstd.
+    ///
+    /// # Errors
+    ///
+    /// Returns a PagerankError if the file can't be opened or is compressed with a format whose
+    /// feature is not enabled.
+    pub fn load_edge_list_file_lenient<P: AsRef<Path>>(
+        &mut self,
+        path: P,
+    ) -> Result<LoadReport, PagerankError> {
+        Ok(self.load_edge_list_lenient(open_edge_list(path.as_ref())?))
+    }
+
+    /// Adds the links of an edge list to the graph, failing on the first bad record.
+    ///
+    /// Returns the number of links added. The links read before an error are kept.
+    ///
+    /// # Arguments
+    ///
+    /// * reader - The edge list, e.g. a BufReader over a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns a PagerankError if a line is malformed, a link exceeds the graph's capacity or
+    /// reading fails.
+    ///
+    /// # Examples
+    ///
+    /// let file = BufReader::new(File::open("edges.txt")?);
+    /// let loaded = pagerank.load_edge_list(file)?;
+    ///
+    pub fn load_edge_list<R: BufRead>(&mut self, reader: R) -> Result<usize, PagerankError> {
+        let mut loaded = 0;
+        for_each_record(reader, |line, record| {
+            let with_line = |error: PagerankError| {
+                PagerankError::ParseError(format!("Line {}: {}", line, error))
+            };
+            if let Record::Link(from, to, edge_type) = record.map_err(with_line)? {
+                self.link_with_type(from, to, edge_type)?;
+                loaded += 1;
+            }
+            Ok(())
+        })?;
+        Ok(loaded)
+    }
+
+    /// Adds the links of an edge list to the graph, skipping the records that are malformed or
+    /// don't fit within the graph's capacity instead of failing.
+    ///
+    /// Reading stops at the first read error, which is reported like a skipped record.
+    ///
+    /// # Arguments
+    ///
+    /// * reader - The edge list, e.g. a BufReader over a file.
+    ///
+    /// # Examples
+    ///
+    /// let report = pagerank.load_edge_list_lenient(BufReader::new(File::open("edges.txt")?));
+    /// println!("Loaded {} links, skipped {}", report.loaded, report.skipped);
+    ///
+    pub fn load_edge_list_lenient<R: BufRead>(&mut self, reader: R) -> LoadReport {
+        let mut report = LoadReport::default();
+        let mut last_line = 0;
+        let result = for_each_record(reader, |line, record| {
+            last_line = line;
+            match record.and_then(|record| match record {
+                Record::Link(from, to, edge_type) => {
+                    self.link_with_type(from, to, edge_type).map(|_| 1)
+                }
+                Record::Ignored => Ok(0),
+            }) {
+                Ok(loaded) => report.loaded += loaded,
+                Err(error) => report.skip(line, error),
+            }
+            Ok(())
+        });
+
+        if let Err(error) = result {
+            report.skip(last_line + 1, error);
+        }
+        report
+    }
+}
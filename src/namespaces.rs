@@ -0,0 +1,185 @@
+//! This module provides PagerankNamespaces, a container of many logically separate graphs that
+//! share one thread pool, for services ranking a large number of small graphs (e.g. one per
+//! community) where a Pagerank and a pool per graph would add up.
+use crate::config::RankConfig;
+use crate::errors::PagerankError;
+use crate::Pagerank;
+use rayon::prelude::*;
+use std::collections::HashMap;
+
+/// The capacity a namespace starts with, it grows on demand up to the capacity of the container.
+const INITIAL_NAMESPACE_CAPACITY: usize = 16;
+
+/// A collection of independent graphs identified by a namespace.
+///
+/// The graph of a namespace is created on its first link, so unused namespaces cost nothing, and
+/// starts small, doubling its capacity whenever it is full until it reaches the capacity of the
+/// container, so small namespaces only take memory for the nodes they have.
+/// Links never cross namespaces and every namespace is ranked on its own.
+///
+/// Fields:
+/// - graphs: The graph of every namespace with at least one node.
+/// - capacity: The maximum number of nodes in the graph of each namespace.
+/// - pool: The thread pool all the computations run on, None for the global rayon pool.
+#[derive(Debug)]
+pub struct PagerankNamespaces {
+    graphs: HashMap<usize, Pagerank>,
+    capacity: usize,
+    pool: Option<rayon::ThreadPool>,
+}
+
+impl PagerankNamespaces {
+    /// Constructs an empty container whose computations run on the global rayon pool.
+    ///
+    /// # Arguments
+    ///
+    /// * capacity - The maximum number of nodes in the graph of each namespace.
+    ///
+    /// # Examples
+    ///
+    /// let mut communities = PagerankNamespaces::new(1_000);
+    ///
+    pub fn new(capacity: usize) -> PagerankNamespaces {
+        PagerankNamespaces {
+            graphs: HashMap::new(),
+            capacity,
+            pool: None,
+        }
+    }
+
+    /// Constructs an empty container whose computations run on a dedicated pool of threads
+    /// threads, built once and shared by all the namespaces.
+    ///
+    /// Falls back to the global rayon pool if the pool can't be built.
+    ///
+    /// # Arguments
+    ///
+    /// * capacity - The maximum number of nodes in the graph of each namespace.
+    /// * threads - The number of threads of the shared pool.
+    pub fn with_threads(capacity: usize, threads: usize) -> PagerankNamespaces {
+        PagerankNamespaces {
+            pool: rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .ok(),
+            ..PagerankNamespaces::new(capacity)
+        }
+    }
+
+    /// Adds a directed link from the from node to the to node in the graph of namespace.
+    ///
+    /// # Errors
+    ///
+    /// Returns a PagerankError if adding the link would exceed the capacity of the namespace, or
+    /// if the graph of the namespace can't grow to hold it.
+    ///
+    /// # Examples
+    ///
+    /// communities.link(community_id, 1, 2).unwrap();
+    ///
+    pub fn link(&mut self, namespace: usize, from: usize, to: usize) -> Result<(), PagerankError> {
+        let capacity = self.capacity;
+        let graph = self
+            .graphs
+            .entry(namespace)
+            .or_insert_with(|| Pagerank::new(capacity.min(INITIAL_NAMESPACE_CAPACITY)));
+
+        let result = loop {
+            match graph.link(from, to) {
+                Err(PagerankError::CapacityError(_)) if graph.capacity() < capacity => {
+                    let additional = graph.capacity().max(1).min(capacity - graph.capacity());
+                    if let Err(e) = graph.grow_capacity(additional) {
+                        break Err(e);
+                    }
+                }
+                result => break result,
+            }
+        };
+
+        // A failed link adds no node, so the first link of the namespace left an empty graph.
+        if graph.size() == 0 {
+            self.graphs.remove(&namespace);
+        }
+        result
+    }
+
+    /// Returns the graph of namespace, if it has any node.
+    pub fn graph(&self, namespace: usize) -> Option<&Pagerank> {
+        self.graphs.get(&namespace)
+    }
+
+    /// Removes namespace and returns its graph, if it had any node.
+    pub fn remove(&mut self, namespace: usize) -> Option<Pagerank> {
+        self.graphs.remove(&namespace)
+    }
+
+    /// Returns the namespaces with at least one node, in no particular order.
+    pub fn namespaces(&self) -> impl Iterator<Item = usize> + '_ {
+        self.graphs.keys().copied()
+    }
+
+    /// Returns a copy of config running on the shared pool instead of a pool of its own.
+    fn shared_pool_config(config: &RankConfig) -> RankConfig {
+        RankConfig {
+            threads: None,
+            ..config.clone()
+        }
+    }
+
+    fn install<R, OP>(&self, op: OP) -> R
+    where
+        R: Send,
+        OP: FnOnce() -> R + Send,
+    {
+        match &self.pool {
+            Some(pool) => pool.install(op),
+            None => op(),
+        }
+    }
+
+    /// Computes the PageRank scores of the graph of namespace on the shared pool.
+    ///
+    /// config.threads is ignored. The result is sorted by descending score and is empty if the
+    /// namespace has no node.
+    ///
+    /// # Arguments
+    ///
+    /// * namespace - The namespace to rank.
+    /// * config - The parameters of the computation.
+    pub fn rank(&self, namespace: usize, config: &RankConfig) -> Vec<(usize, f64)> {
+        let Some(graph) = self.graphs.get(&namespace) else {
+            return Vec::new();
+        };
+
+        let config = Self::shared_pool_config(config);
+        self.install(|| graph.rank_with_config(&config))
+    }
+
+    /// Computes the PageRank scores of every namespace on the shared pool.
+    ///
+    /// The namespaces are ranked in parallel, which keeps all the threads busy even when the
+    /// graphs are too small to split a single computation between threads. config.threads is
+    /// ignored.
+    ///
+    /// # Arguments
+    ///
+    /// * config - The parameters of the computations.
+    ///
+    /// # Examples
+    ///
+    /// for (community_id, ranked) in communities.rank_all(&RankConfig::default()) {
+    ///     store(community_id, ranked);
+    /// }
+    ///
+    pub fn rank_all(&self, config: &RankConfig) -> HashMap<usize, Vec<(usize, f64)>> {
+        let config = Self::shared_pool_config(config);
+        let graphs: Vec<(&usize, &Pagerank)> = self.graphs.iter().collect();
+
+        self.install(|| {
+            graphs
+                .into_par_iter()
+                .map(|(&namespace, graph)| (namespace, graph.rank_with_config(&config)))
+                .collect()
+        })
+    }
+}
@@ -7,10 +7,16 @@
 //! and managing the underlying graph data. It uses a simple iterative approach to converge to the
 //! steady-state distribution of the PageRank values. The implementation leverages parallel computation
 //! to improve performance on multi-core systems.
+use crate::config::{InitialDistribution, RankConfig, Solver, TieBreak};
 use crate::errors::PagerankError;
+use crate::storage::{ChunkedVec, ShardedMap};
 use rayon::prelude::*;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::fmt::{self, Display, Formatter};
+use std::io::{self, Write};
+use std::ops::Deref;
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, OnceLock};
 
 /// A structure for managing and computing PageRank scores for nodes in a graph.
 ///
@@ -22,38 +28,128 @@ use std::fmt::{self, Display, Formatter};
 /// Fields:
 /// - in_links: A vector of vectors where each sub-vector contains the indices of nodes
 ///   that have an outgoing link to the node at the corresponding index.
+/// - in_link_types: A vector of vectors parallel to in_links holding the type of each link.
+/// - in_link_provenance: A vector of vectors parallel to in_links holding the provenance tag of
+///   each link. None until the first link with a provenance tag is added, so graphs that don't
+///   track provenance don't pay for it.
 /// - number_out_links: A vector where each element is the number of outgoing links
 ///   from the node at the corresponding index.
 /// - current_available_index: The next available index for assigning to a new node.
 /// - key_to_index: A mapping from node identifiers to their indices in the graph vectors.
-/// - index_to_key: A mapping from indices in the graph vectors to node identifiers.
+/// - index_to_key: The node identifier of each index in the graph vectors, indices are dense.
+/// - last_from: The key and index of the source of the last link, consecutive links often
+///   share their source so this saves a key_to_index lookup.
 /// - capacity: The maximum number of nodes the Pagerank instance can handle.// and managing the underlying graph data.
+///
+/// The graph data is stored in reference counted chunks of CHUNK_SIZE nodes, so cloning a
+/// Pagerank or taking a snapshot only copies the chunk pointers. A mutation after the data has
+/// been shared copies the chunks it touches, e.g. adding a link copies the chunk of its target,
+/// the chunk of its source and, for new nodes, one shard of the key index.
+#[derive(Debug, Clone)]
 pub struct Pagerank {
-    in_links: Vec<Vec<usize>>,
-    number_out_links: Vec<usize>,
+    in_links: ChunkedVec<Vec<usize>>,
+    in_link_types: ChunkedVec<Vec<EdgeType>>,
+    in_link_provenance: Option<ChunkedVec<Vec<Provenance>>>,
+    number_out_links: ChunkedVec<usize>,
     current_available_index: usize,
-    key_to_index: HashMap<usize, usize>,
-    index_to_key: HashMap<usize, usize>,
+    key_to_index: ShardedMap,
+    index_to_key: ChunkedVec<usize>,
+    last_from: Option<(usize, usize)>,
     capacity: usize,
 }
 
+/// A rank result with the nodes whose score is below RankConfig::drop_below left out.
+///
+/// Fields:
+/// - ranked: The kept scores, sorted by descending score.
+/// - dropped: The number of nodes left out.
+/// - dropped_mass: The sum of the converged scores of the nodes left out.
+#[derive(Debug, Clone, PartialEq)]
+pub struct PrunedRank {
+    pub ranked: Vec<(usize, f64)>,
+    pub dropped: usize,
+    pub dropped_mass: f64,
+}
+
+/// A small tag attached to each link, used to rank different views of the same graph.
+///
+/// Links added with Pagerank::link have the type DEFAULT_EDGE_TYPE.
+pub type EdgeType = u8;
+
+/// The type of the links added with Pagerank::link.
+pub const DEFAULT_EDGE_TYPE: EdgeType = 0;
+
+/// A compact tag recording where a link comes from (e.g. the ID of its import batch), so
+/// scores can be traced back to their input data.
+pub type Provenance = u32;
+
+/// The provenance of the links added without a provenance tag. Reserved, so it can't be passed
+/// to Pagerank::link_with_provenance.
+pub const NO_PROVENANCE: Provenance = 0;
+
+/// Orderings of the internal node indices that can be applied with Pagerank::reorder.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NodeOrder {
+    /// Nodes with more links first, so the most read scores are packed together.
+    Degree,
+    /// Breadth-first order over the links in both directions, so linked nodes get close indices.
+    Bfs,
+    /// Reverse Cuthill-McKee order: a breadth-first order starting from and visiting lower
+    /// degree nodes first, reversed. It usually gives the best locality on sparse graphs.
+    ReverseCuthillMckee,
+}
+
+/// A logically immutable view of a Pagerank graph at the time the snapshot was taken.
+///
+/// Snapshots share the graph data with the Pagerank they were taken from, so they are cheap to
+/// create and can be sent to another thread for ranking while the original keeps accepting
+/// links. All the read-only methods of Pagerank, such as rank, are available through Deref.
+#[derive(Debug, Clone)]
+pub struct PagerankSnapshot {
+    graph: Pagerank,
+}
+
+impl Deref for PagerankSnapshot {
+    type Target = Pagerank;
+
+    fn deref(&self) -> &Pagerank {
+        &self.graph
+    }
+}
+
+/// Per computation data derived from the graph and the RankConfig.
+///
+/// Fields:
+/// - inverse_out_links: For each node index, the share of its score sent through each of its
+///   outgoing links, or 0 if it doesn't emit score through links.
+/// - dangling_nodes: The indices of the nodes whose score is redistributed through teleport.
+/// - teleport: For each node index, its share of the teleported score.
+/// - allowed_edge_types: When ranking a filtered view, whether each edge type is followed.
+/// - reversed: Whether the links are followed in the opposite direction.
+pub(crate) struct RankSetup {
+    pub(crate) inverse_out_links: Vec<f64>,
+    pub(crate) dangling_nodes: Vec<usize>,
+    pub(crate) teleport: Vec<f64>,
+    pub(crate) allowed_edge_types: Option<[bool; 256]>,
+    pub(crate) reversed: bool,
+}
+
+/// A one line summary of the graph, use Pagerank::dump to print its structure.
 impl Display for Pagerank {
     fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let size = self.size();
+        let links: usize = self.number_out_links.iter().take(size).sum();
+        let dangling = self
+            .number_out_links
+            .iter()
+            .take(size)
+            .filter(|&&count| count == 0)
+            .count();
+
         write!(
             f,
-            "Pagerank Struct:\n\
-             InLinks: {:?}\n\
-             NumberOutLinks: {:?}\n\
-             CurrentAvailableIndex: {}\n\
-             KeyToIndex: {:?}\n\
-             IndexToKey: {:?}\n\
-             Capacity: {}",
-            self.in_links,
-            self.number_out_links,
-            self.current_available_index,
-            self.key_to_index,
-            self.index_to_key,
-            self.capacity
+            "Pagerank: {} nodes, {} links, {} dangling nodes, capacity {}",
+            size, links, dangling, self.capacity
         )
     }
 }
@@ -74,42 +170,131 @@ impl Pagerank {
     ///
     pub fn new(capacity: usize) -> Pagerank {
         Pagerank {
-            in_links: vec![Vec::with_capacity(capacity); capacity],
-            number_out_links: vec![0; capacity],
+            in_links: ChunkedVec::from_elem(Vec::new(), capacity),
+            in_link_types: ChunkedVec::from_elem(Vec::new(), capacity),
+            in_link_provenance: None,
+            number_out_links: ChunkedVec::from_elem(0, capacity),
             current_available_index: 0,
-            key_to_index: HashMap::with_capacity(capacity),
-            index_to_key: HashMap::with_capacity(capacity),
+            key_to_index: ShardedMap::with_capacity(capacity),
+            index_to_key: ChunkedVec::new(),
+            last_from: None,
             capacity,
         }
     }
 
-    fn key_as_array_index(&mut self, key: usize) -> Result<usize, PagerankError> {
-        if self.current_available_index > self.capacity {
+    pub(crate) fn key_as_array_index(&mut self, key: usize) -> Result<usize, PagerankError> {
+        if let Some(&index) = self.key_to_index.get(&key) {
+            return Ok(index);
+        }
+        self.check_room_for(1)?;
+
+        let new_index = self.current_available_index;
+        self.key_to_index.insert(key, new_index);
+        self.index_to_key.push(key);
+        self.current_available_index += 1;
+        Ok(new_index)
+    }
+
+    /// Returns a CapacityError if adding new_nodes nodes would exceed the capacity.
+    fn check_room_for(&self, new_nodes: usize) -> Result<(), PagerankError> {
+        if self.current_available_index + new_nodes > self.capacity {
             let message = format!(
                 "Exceeded the capacity of nodes, current available index: {}, capacity: {}",
                 self.current_available_index, self.capacity,
             );
             return Err(PagerankError::CapacityError(message));
         }
-        let index = self.key_to_index.entry(key).or_insert_with(|| {
-            let new_index = self.current_available_index;
-            self.index_to_key.insert(new_index, key);
-            self.current_available_index += 1;
-            new_index
-        });
-        Ok(*index)
+        Ok(())
+    }
+
+    /// Returns the number of nodes in the graph.
+    pub(crate) fn size(&self) -> usize {
+        self.current_available_index
+    }
+
+    pub(crate) fn index_of(&self, key: usize) -> Option<usize> {
+        self.key_to_index.get(&key).copied()
+    }
+
+    pub(crate) fn key_of(&self, index: usize) -> usize {
+        self.index_to_key[index]
+    }
+
+    pub(crate) fn in_links_of(&self, index: usize) -> &[usize] {
+        &self.in_links[index]
+    }
+
+    pub(crate) fn in_link_types_of(&self, index: usize) -> &[EdgeType] {
+        &self.in_link_types[index]
     }
 
-    fn update_in_links(&mut self, from_as_index: usize, to_as_index: usize) {
+    pub(crate) fn number_out_links_of(&self, index: usize) -> usize {
+        self.number_out_links[index]
+    }
+
+    /// Returns the number of node and link visits of an iteration.
+    pub(crate) fn visits_per_iteration(&self) -> usize {
+        let links: usize = self.in_links.iter().take(self.size()).map(Vec::len).sum();
+        self.size() + links
+    }
+
+    /// Returns an estimate of the heap memory used by the graph, in bytes. Walks every node slot
+    /// up to the capacity, so it is only computed once per computation.
+    #[cfg(feature = "metrics")]
+    pub(crate) fn memory_bytes(&self) -> usize {
+        use std::mem::size_of;
+
+        let in_links: usize = self.in_links.iter().map(Vec::capacity).sum();
+        let in_link_types: usize = self.in_link_types.iter().map(Vec::capacity).sum();
+
+        self.in_links.heap_bytes()
+            + in_links * size_of::<usize>()
+            + self.in_link_types.heap_bytes()
+            + in_link_types * size_of::<EdgeType>()
+            + self.number_out_links.heap_bytes()
+            + self.key_to_index.heap_bytes()
+            + self.index_to_key.heap_bytes()
+            + self.in_link_provenance.as_ref().map_or(0, |provenance| {
+                let tags: usize = provenance.iter().map(Vec::capacity).sum();
+                provenance.heap_bytes() + tags * size_of::<Provenance>()
+            })
+    }
+
+    fn update_in_links(
+        &mut self,
+        from_as_index: usize,
+        to_as_index: usize,
+        edge_type: EdgeType,
+        provenance: Provenance,
+    ) {
+        if provenance != NO_PROVENANCE && self.in_link_provenance.is_none() {
+            let untagged: Vec<Vec<Provenance>> = self
+                .in_links
+                .iter()
+                .map(|links| vec![NO_PROVENANCE; links.len()])
+                .collect();
+            self.in_link_provenance = Some(ChunkedVec::from(untagged));
+        }
+
         self.in_links[to_as_index].push(from_as_index);
+        self.in_link_types[to_as_index].push(edge_type);
+        if let Some(in_link_provenance) = self.in_link_provenance.as_mut() {
+            in_link_provenance[to_as_index].push(provenance);
+        }
     }
 
     fn update_number_out_links(&mut self, from_as_index: usize) {
         self.number_out_links[from_as_index] += 1;
     }
 
-    fn link_with_indices(&mut self, from_as_index: usize, to_as_index: usize) {
-        self.update_in_links(from_as_index, to_as_index);
+    fn link_with_indices(
+        &mut self,
+        from_as_index: usize,
+        to_as_index: usize,
+        edge_type: EdgeType,
+        provenance: Provenance,
+    ) {
+        self.update_in_links(from_as_index, to_as_index, edge_type, provenance);
         self.update_number_out_links(from_as_index);
     }
 
@@ -133,50 +318,680 @@ impl Pagerank {
     /// pagerank.link(1, 2).unwrap();
     ///
     pub fn link(&mut self, from: usize, to: usize) -> Result<(), PagerankError> {
-        let from_as_index = self.key_as_array_index(from)?;
-        let to_as_index = self.key_as_array_index(to)?;
+        self.link_with_type(from, to, DEFAULT_EDGE_TYPE)
+    }
 
-        self.link_with_indices(from_as_index, to_as_index);
+    /// Adds a directed link of the given type from the from node to the to node.
+    ///
+    /// The type can later be used to rank only a subset of the links with rank_filtered.
+    ///
+    /// # Arguments
+    ///
+    /// * from - The index of the node where the link originates.
+    /// * to - The index of the node where the link points to.
+    /// * edge_type - The type tag of the link.
+    ///
+    /// # Errors
+    ///
+    /// Returns a PagerankError if adding the link would exceed the graph's capacity.
+    ///
+    /// # Examples
+    ///
+    /// const MENTION: EdgeType = 1;
+    /// pagerank.link_with_type(1, 2, MENTION).unwrap();
+    ///
+    pub fn link_with_type(
+        &mut self,
+        from: usize,
+        to: usize,
+        edge_type: EdgeType,
+    ) -> Result<(), PagerankError> {
+        self.link_tagged(from, to, edge_type, NO_PROVENANCE)
+    }
+
+    /// Adds a directed link of the given type from the from node to the to node, tagged with
+    /// where the link comes from.
+    ///
+    /// The tag is only stored for auditing, it doesn't change the scores. The tags of the links
+    /// pointing to a node are returned by in_link_provenance and provenance_summary.
+    ///
+    /// # Arguments
+    ///
+    /// * from - The index of the node where the link originates.
+    /// * to - The index of the node where the link points to.
+    /// * edge_type - The type tag of the link.
+    /// * provenance - The provenance tag of the link, e.g. the ID of its import batch. Must not
+    ///   be NO_PROVENANCE, which marks the links added without a tag.
+    ///
+    /// # Errors
+    ///
+    /// Returns a ProvenanceError if provenance is NO_PROVENANCE, and a PagerankError if adding
+    /// the link would exceed the graph's capacity.
+    ///
+    /// # Examples
+    ///
+    /// pagerank.link_with_provenance(1, 2, DEFAULT_EDGE_TYPE, batch_id).unwrap();
+    ///
+    pub fn link_with_provenance(
+        &mut self,
+        from: usize,
+        to: usize,
+        edge_type: EdgeType,
+        provenance: Provenance,
+    ) -> Result<(), PagerankError> {
+        if provenance == NO_PROVENANCE {
+            return Err(PagerankError::ProvenanceError(format!(
+                "The provenance tag {} is reserved for links added without a tag",
+                NO_PROVENANCE
+            )));
+        }
+        self.link_tagged(from, to, edge_type, provenance)
+    }
+
+    fn link_tagged(
+        &mut self,
+        from: usize,
+        to: usize,
+        edge_type: EdgeType,
+        provenance: Provenance,
+    ) -> Result<(), PagerankError> {
+        let known_from = match self.last_from {
+            Some((key, index)) if key == from => Some(index),
+            _ => self.index_of(from),
+        };
+        let known_to = self.index_of(to);
+        // Both nodes must fit before either is added, so a failing link leaves no node behind.
+        let new_nodes =
+            usize::from(known_from.is_none()) + usize::from(known_to.is_none() && to != from);
+        self.check_room_for(new_nodes)?;
+
+        let from_as_index = match known_from {
+            Some(index) => index,
+            None => self.key_as_array_index(from)?,
+        };
+        self.last_from = Some((from, from_as_index));
+        let to_as_index = match known_to {
+            Some(index) => index,
+            None => self.key_as_array_index(to)?,
+        };
+
+        self.link_with_indices(from_as_index, to_as_index, edge_type, provenance);
         Ok(())
     }
 
-    fn calculate_dangling_nodes(&self) -> Vec<usize> {
-        self.number_out_links
+    /// Returns the source and provenance tag of every link pointing to key, in no particular
+    /// order: relink and reorder move links around. Links added without a tag have the
+    /// NO_PROVENANCE tag.
+    ///
+    /// # Arguments
+    ///
+    /// * key - The node whose incoming links are listed.
+    pub fn in_link_provenance(&self, key: usize) -> Vec<(usize, Provenance)> {
+        let Some(index) = self.index_of(key) else {
+            return Vec::new();
+        };
+
+        self.in_links[index]
             .iter()
-            .take(self.current_available_index)
             .enumerate()
-            .filter(|&(_index, &out_links_count)| out_links_count == 0)
+            .map(|(position, &from_as_index)| {
+                let provenance = self
+                    .in_link_provenance
+                    .as_ref()
+                    .map_or(NO_PROVENANCE, |provenance| provenance[index][position]);
+                (self.index_to_key[from_as_index], provenance)
+            })
+            .collect()
+    }
+
+    /// Returns the provenance tags that contributed links pointing to key, with the number of
+    /// links carrying each tag, sorted by tag.
+    ///
+    /// # Arguments
+    ///
+    /// * key - The node whose incoming links are summarized.
+    ///
+    /// # Examples
+    ///
+    /// for (batch_id, links) in pagerank.provenance_summary(suspicious_node) {
+    ///     println!("Batch {} contributed {} links", batch_id, links);
+    /// }
+    ///
+    pub fn provenance_summary(&self, key: usize) -> Vec<(Provenance, usize)> {
+        let mut summary: Vec<(Provenance, usize)> = Vec::new();
+        let mut tags: Vec<Provenance> = self
+            .in_link_provenance(key)
+            .into_iter()
+            .map(|(_, provenance)| provenance)
+            .collect();
+        tags.sort_unstable();
+
+        for provenance in tags {
+            match summary.last_mut() {
+                Some((last, count)) if *last == provenance => *count += 1,
+                _ => summary.push((provenance, 1)),
+            }
+        }
+        summary
+    }
+
+    fn remove_in_links_from(
+        in_links: &mut Vec<usize>,
+        in_link_types: &mut Vec<EdgeType>,
+        from_as_index: usize,
+    ) -> usize {
+        let mut kept = 0;
+        for i in 0..in_links.len() {
+            if in_links[i] != from_as_index {
+                in_links[kept] = in_links[i];
+                in_link_types[kept] = in_link_types[i];
+                kept += 1;
+            }
+        }
+
+        let removed = in_links.len() - kept;
+        in_links.truncate(kept);
+        in_link_types.truncate(kept);
+        removed
+    }
+
+    /// Removes every outgoing link of the from node, returning the number of links removed.
+    ///
+    /// The node itself stays in the graph, becoming a dangling node. Unknown nodes have no
+    /// links, so nothing is removed for them.
+    ///
+    /// # Arguments
+    ///
+    /// * from - The node whose outgoing links are removed.
+    ///
+    /// # Examples
+    ///
+    /// // The page at key 1 was a duplicate URL, drop everything it linked to.
+    /// let removed = pagerank.unlink_all_from(1);
+    ///
+    pub fn unlink_all_from(&mut self, from: usize) -> usize {
+        let Some(&from_as_index) = self.key_to_index.get(&from) else {
+            return 0;
+        };
+        if self.number_out_links[from_as_index] == 0 {
+            return 0;
+        }
+
+        // Only the chunks holding a link from the node are copied if the graph is shared.
+        let touched: Vec<bool> = self
+            .in_links
+            .chunks()
+            .par_iter()
+            .map(|chunk| chunk.iter().any(|links| links.contains(&from_as_index)))
+            .collect();
+        if let Some(in_link_provenance) = self.in_link_provenance.as_mut() {
+            // Compacted first, the positions of the links to remove are read from in_links.
+            in_link_provenance
+                .chunks_mut()
+                .par_iter_mut()
+                .zip(self.in_links.chunks().par_iter())
+                .zip(touched.par_iter())
+                .filter(|(_, &touched)| touched)
+                .for_each(|((tags, links), _)| {
+                    for (tags, links) in Arc::make_mut(tags).iter_mut().zip(links.iter()) {
+                        let mut position = 0;
+                        tags.retain(|_| {
+                            position += 1;
+                            links[position - 1] != from_as_index
+                        });
+                    }
+                });
+        }
+        let removed: usize = self
+            .in_links
+            .chunks_mut()
+            .par_iter_mut()
+            .zip(self.in_link_types.chunks_mut().par_iter_mut())
+            .zip(touched.par_iter())
+            .filter(|(_, &touched)| touched)
+            .map(|((links, types), _)| {
+                Arc::make_mut(links)
+                    .iter_mut()
+                    .zip(Arc::make_mut(types).iter_mut())
+                    .map(|(links, types)| Self::remove_in_links_from(links, types, from_as_index))
+                    .sum::<usize>()
+            })
+            .sum();
+
+        self.number_out_links[from_as_index] = 0;
+        removed
+    }
+
+    /// Moves a link from the from node to old_to so that it points to new_to instead.
+    ///
+    /// The number of outgoing links of the from node doesn't change and the link keeps its type
+    /// and provenance tag.
+    /// If the link was added several times, only one of the copies is moved.
+    ///
+    /// # Arguments
+    ///
+    /// * from - The node where the link originates.
+    /// * old_to - The node the link currently points to.
+    /// * new_to - The node the link will point to. It is created if it doesn't exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns a PagerankError if there is no link from from to old_to, or if creating new_to
+    /// would exceed the graph's capacity. The graph is left unchanged in both cases.
+    ///
+    /// # Examples
+    ///
+    /// // http://example.com was canonicalized to https://example.com
+    /// pagerank.relink(1, 2, 3).unwrap();
+    ///
+    pub fn relink(
+        &mut self,
+        from: usize,
+        old_to: usize,
+        new_to: usize,
+    ) -> Result<(), PagerankError> {
+        let link_position = self
+            .key_to_index
+            .get(&from)
+            .zip(self.key_to_index.get(&old_to))
+            .and_then(|(&from_as_index, &old_to_as_index)| {
+                self.in_links[old_to_as_index]
+                    .iter()
+                    .position(|&index| index == from_as_index)
+                    .map(|position| (old_to_as_index, position))
+            });
+        let Some((old_to_as_index, position)) = link_position else {
+            let message = format!("There is no link from {} to {}", from, old_to);
+            return Err(PagerankError::LinkNotFoundError(message));
+        };
+        let new_to_as_index = self.key_as_array_index(new_to)?;
+
+        let from_as_index = self.in_links[old_to_as_index].swap_remove(position);
+        let edge_type = self.in_link_types[old_to_as_index].swap_remove(position);
+        let provenance = self
+            .in_link_provenance
+            .as_mut()
+            .map_or(NO_PROVENANCE, |provenance| {
+                provenance[old_to_as_index].swap_remove(position)
+            });
+        self.update_in_links(from_as_index, new_to_as_index, edge_type, provenance);
+        Ok(())
+    }
+
+    fn calculate_filtered_out_links(&self, allowed_edge_types: &[bool; 256]) -> Vec<usize> {
+        let size = self.current_available_index;
+
+        // A single shared buffer of atomic counters, for the reason given on atomic_add.
+        let counts: Vec<AtomicUsize> = (0..size).map(|_| AtomicUsize::new(0)).collect();
+        (0..size).into_par_iter().for_each(|i| {
+            self.in_links[i]
+                .iter()
+                .zip(&self.in_link_types[i])
+                .filter(|&(_, &edge_type)| allowed_edge_types[edge_type as usize])
+                .for_each(|(&index, _)| {
+                    counts[index].fetch_add(1, Ordering::Relaxed);
+                });
+        });
+        counts.into_iter().map(AtomicUsize::into_inner).collect()
+    }
+
+    /// Returns the share of score the node with key sends through each of its out_links outgoing
+    /// links, or 0 if it doesn't emit score through links.
+    #[inline]
+    pub(crate) fn inverse_out_links_of(config: &RankConfig, key: usize, out_links: usize) -> f64 {
+        if out_links == 0 || config.no_emit.contains(&key) {
+            0.0
+        } else {
+            1.0 / out_links as f64
+        }
+    }
+
+    /// Returns the teleport of the node with key, before it is normalized by the sum of the
+    /// teleport of all nodes.
+    #[inline]
+    pub(crate) fn unnormalized_teleport(config: &RankConfig, key: usize) -> f64 {
+        if config.no_teleport.contains(&key) {
+            0.0
+        } else {
+            1.0
+        }
+    }
+
+    /// Normalizes the teleport of a node by receivers, the sum of the unnormalized teleport of
+    /// the size nodes.
+    #[inline]
+    pub(crate) fn normalized_teleport(teleport: f64, receivers: f64, size: usize) -> f64 {
+        if receivers == 0.0 {
+            // Every node is excluded, fall back to a uniform teleport to keep the scores defined.
+            1.0 / size as f64
+        } else {
+            teleport / receivers
+        }
+    }
+
+    /// Returns the index of the source node of an InitialDistribution::Source in the graph.
+    pub(crate) fn initial_source(&self, config: &RankConfig) -> Option<usize> {
+        match &config.initial {
+            InitialDistribution::Source(key) => self.index_of(*key),
+            _ => None,
+        }
+    }
+
+    /// Returns the initial score of the node at index i according to config.initial, before it
+    /// is normalized. The scores of a warm start are set over these ones.
+    pub(crate) fn unnormalized_initial_score(
+        &self,
+        config: &RankConfig,
+        source: Option<usize>,
+        i: usize,
+    ) -> f64 {
+        match &config.initial {
+            InitialDistribution::Uniform | InitialDistribution::TeleportProportional => 1.0,
+            InitialDistribution::WarmStart(_) => 1.0 / self.size() as f64,
+            InitialDistribution::DegreeProportional => (self.in_links[i].len() + 1) as f64,
+            InitialDistribution::Source(_) => match source {
+                Some(source) if source == i => 1.0,
+                Some(_) => 0.0,
+                None => 1.0,
+            },
+        }
+    }
+
+    /// Normalizes the initial score p_i of a node by sum, the sum of the unnormalized initial
+    /// scores of the size nodes. teleport is the normalized teleport of the node.
+    #[inline]
+    pub(crate) fn normalized_initial_score(
+        config: &RankConfig,
+        p_i: f64,
+        sum: f64,
+        teleport: f64,
+        size: usize,
+    ) -> f64 {
+        if matches!(config.initial, InitialDistribution::TeleportProportional) {
+            teleport
+        } else if sum.is_finite() && sum > 0.0 {
+            p_i / sum
+        } else {
+            1.0 / size as f64
+        }
+    }
+
+    fn calculate_inverse_out_links(
+        &self,
+        config: &RankConfig,
+        number_out_links: &[usize],
+    ) -> Vec<f64> {
+        (0..number_out_links.len())
+            .into_par_iter()
+            .map(|i| Self::inverse_out_links_of(config, self.key_of(i), number_out_links[i]))
+            .collect()
+    }
+
+    fn calculate_dangling_nodes(inverse_out_links: &[f64]) -> Vec<usize> {
+        inverse_out_links
+            .iter()
+            .enumerate()
+            .filter(|&(_index, &inverse)| inverse == 0.0)
             .map(|(index, _)| index)
             .collect()
     }
 
-    fn step(
+    fn calculate_teleport(&self, config: &RankConfig) -> Vec<f64> {
+        let size = self.current_available_index;
+        let teleport: Vec<f64> = (0..size)
+            .into_par_iter()
+            .map(|i| Self::unnormalized_teleport(config, self.key_of(i)))
+            .collect();
+
+        let receivers: f64 = teleport.iter().sum();
+        teleport
+            .into_par_iter()
+            .map(|t| Self::normalized_teleport(t, receivers, size))
+            .collect()
+    }
+
+    pub(crate) fn setup(
         &self,
+        config: &RankConfig,
+        allowed_edge_types: Option<[bool; 256]>,
+    ) -> RankSetup {
+        let inverse_out_links = match &allowed_edge_types {
+            Some(allowed) => self
+                .calculate_inverse_out_links(config, &self.calculate_filtered_out_links(allowed)),
+            None => {
+                let number_out_links: Vec<usize> = (0..self.current_available_index)
+                    .into_par_iter()
+                    .map(|i| self.number_out_links[i])
+                    .collect();
+                self.calculate_inverse_out_links(config, &number_out_links)
+            }
+        };
+        let dangling_nodes = Self::calculate_dangling_nodes(&inverse_out_links);
+        let teleport = self.calculate_teleport(config);
+
+        RankSetup {
+            inverse_out_links,
+            dangling_nodes,
+            teleport,
+            allowed_edge_types,
+            reversed: false,
+        }
+    }
+
+    fn setup_reversed(&self, config: &RankConfig) -> RankSetup {
+        // On the reversed graph the incoming links of a node become its outgoing links.
+        let number_out_links: Vec<usize> = (0..self.current_available_index)
+            .into_par_iter()
+            .map(|i| self.in_links[i].len())
+            .collect();
+        let inverse_out_links = self.calculate_inverse_out_links(config, &number_out_links);
+        let dangling_nodes = Self::calculate_dangling_nodes(&inverse_out_links);
+        let teleport = self.calculate_teleport(config);
+
+        RankSetup {
+            inverse_out_links,
+            dangling_nodes,
+            teleport,
+            allowed_edge_types: None,
+            reversed: true,
+        }
+    }
+
+    #[inline]
+    pub(crate) fn node_rank(
         following_prob: f64,
-        t_over_size: f64,
+        rank_sum: f64,
+        dangling_sum: f64,
+        teleport: f64,
+    ) -> f64 {
+        following_prob * (rank_sum + dangling_sum * teleport) + (1.0 - following_prob) * teleport
+    }
+
+    /// Computes the unnormalized rank of the node at index i without using the thread pool.
+    pub(crate) fn sequential_node_rank(
+        &self,
+        following_prob: f64,
+        setup: &RankSetup,
         p: &[f64],
-        dangling_nodes: &[usize],
-        new_p: &mut [f64],
-    ) {
+        dangling_sum: f64,
+        i: usize,
+    ) -> f64 {
+        let rank_sum: f64 = match &setup.allowed_edge_types {
+            Some(allowed) => self.in_links[i]
+                .iter()
+                .zip(&self.in_link_types[i])
+                .filter(|&(_, &edge_type)| allowed[edge_type as usize])
+                .map(|(&index, _)| p[index] * setup.inverse_out_links[index])
+                .sum(),
+            None => self.in_links[i]
+                .iter()
+                .map(|&index| p[index] * setup.inverse_out_links[index])
+                .sum(),
+        };
+
+        Self::node_rank(following_prob, rank_sum, dangling_sum, setup.teleport[i])
+    }
+
+    /// Returns the (index, share) pairs of the incoming links of the node at index i that carry
+    /// score under setup, where share is the fraction of the source's score sent through the link.
+    pub(crate) fn score_links<'a>(
+        &'a self,
+        setup: &'a RankSetup,
+        i: usize,
+    ) -> impl Iterator<Item = (usize, f64)> + 'a {
+        self.in_links[i]
+            .iter()
+            .zip(&self.in_link_types[i])
+            .filter(move |&(_, &edge_type)| match &setup.allowed_edge_types {
+                Some(allowed) => allowed[edge_type as usize],
+                None => true,
+            })
+            .map(move |(&index, _)| (index, setup.inverse_out_links[index]))
+            .filter(|&(_, share)| share != 0.0)
+    }
+
+    pub(crate) fn normalize(new_p: &mut [f64]) {
+        let v_sum: f64 = new_p.par_iter().sum();
+        new_p.par_iter_mut().for_each(|x| *x /= v_sum);
+    }
+
+    /// Performs an iteration over the reversed graph by scattering the score of each node to the
+    /// nodes it has incoming links from with atomic_add, so that no transposed adjacency has to
+    /// be stored.
+    fn step_reversed(&self, following_prob: f64, setup: &RankSetup, p: &[f64], new_p: &mut [f64]) {
         let size = p.len();
-        let inner_product: f64 = dangling_nodes.par_iter().map(|&node| p[node]).sum();
-        let inner_product_over_size = inner_product / size as f64;
+        let dangling_sum: f64 = setup.dangling_nodes.par_iter().map(|&node| p[node]).sum();
+
+        let rank_sums: Vec<AtomicU64> = (0..size).map(|_| AtomicU64::new(0)).collect();
+        (0..size).into_par_iter().for_each(|i| {
+            let share = p[i] * setup.inverse_out_links[i];
+            if share != 0.0 {
+                self.in_links[i]
+                    .iter()
+                    .for_each(|&index| Self::atomic_add(&rank_sums[index], share));
+            }
+        });
 
         new_p.par_iter_mut().enumerate().for_each(|(i, new_p_i)| {
-            let rank_sum: f64 = self.in_links[i]
-                .par_iter()
-                .map(|&index| p[index] / self.number_out_links[index] as f64)
-                .sum();
+            *new_p_i = Self::node_rank(
+                following_prob,
+                f64::from_bits(rank_sums[i].load(Ordering::Relaxed)),
+                dangling_sum,
+                setup.teleport[i],
+            );
+        });
+
+        Self::normalize(new_p);
+    }
 
-            *new_p_i = following_prob * (rank_sum + inner_product_over_size) + t_over_size;
+    /// Adds value to the f64 whose bits are stored in target.
+    ///
+    /// Scatters write to a single shared buffer of atomics, as a buffer per rayon job would take
+    /// memory for threads x nodes. The order of the additions depends on the scheduling of the
+    /// threads, so the sums can differ in their last bits from run to run.
+    fn atomic_add(target: &AtomicU64, value: f64) {
+        let mut current = target.load(Ordering::Relaxed);
+        loop {
+            let sum = (f64::from_bits(current) + value).to_bits();
+            match target.compare_exchange_weak(current, sum, Ordering::Relaxed, Ordering::Relaxed) {
+                Ok(_) => return,
+                Err(actual) => current = actual,
+            }
+        }
+    }
+
+    /// Runs op on every thread of the current pool with the chunk of buffer owned by that thread
+    /// and the index of the first node of the chunk. Each thread always owns the same chunk.
+    fn for_each_thread_chunk<F>(buffer: &mut [f64], op: F)
+    where
+        F: Fn(usize, &mut [f64]) + Sync,
+    {
+        let chunk_size = buffer.len().div_ceil(rayon::current_num_threads()).max(1);
+        let chunks: Vec<Mutex<(usize, &mut [f64])>> = buffer
+            .chunks_mut(chunk_size)
+            .enumerate()
+            .map(|(i, chunk)| Mutex::new((i * chunk_size, chunk)))
+            .collect();
+
+        rayon::broadcast(|context| {
+            if let Some(chunk) = chunks.get(context.index()) {
+                let mut chunk = chunk
+                    .lock()
+                    .unwrap_or_else(|poisoned| poisoned.into_inner());
+                let (start, values) = &mut *chunk;
+                op(*start, values);
+            }
+        });
+    }
+
+    /// Copies values into a new buffer whose pages are first touched by the threads owning them.
+    fn first_touch(values: &[f64]) -> Vec<f64> {
+        // Large zeroed allocations are only backed by memory once they are written to.
+        let mut buffer = vec![0.0; values.len()];
+        Self::for_each_thread_chunk(&mut buffer, |start, chunk| {
+            chunk.copy_from_slice(&values[start..start + chunk.len()]);
+        });
+        buffer
+    }
+
+    /// Performs an iteration where every thread of the pool computes the scores of the same
+    /// range of nodes as in the previous iterations, keeping its writes local to its memory.
+    fn step_pinned(&self, following_prob: f64, setup: &RankSetup, p: &[f64], new_p: &mut [f64]) {
+        let dangling_sum: f64 = setup.dangling_nodes.par_iter().map(|&node| p[node]).sum();
+
+        Self::for_each_thread_chunk(new_p, |start, chunk| {
+            chunk.iter_mut().enumerate().for_each(|(offset, new_p_i)| {
+                *new_p_i = self.sequential_node_rank(
+                    following_prob,
+                    setup,
+                    p,
+                    dangling_sum,
+                    start + offset,
+                );
+            });
         });
 
         let v_sum: f64 = new_p.par_iter().sum();
-        new_p.par_iter_mut().for_each(|x| *x /= v_sum);
+        Self::for_each_thread_chunk(new_p, |_, chunk| {
+            chunk.iter_mut().for_each(|x| *x /= v_sum);
+        });
+    }
+
+    pub(crate) fn step(
+        &self,
+        following_prob: f64,
+        setup: &RankSetup,
+        p: &[f64],
+        new_p: &mut [f64],
+    ) {
+        if setup.reversed {
+            return self.step_reversed(following_prob, setup, p, new_p);
+        }
+
+        let dangling_sum: f64 = setup.dangling_nodes.par_iter().map(|&node| p[node]).sum();
+
+        new_p.par_iter_mut().enumerate().for_each(|(i, new_p_i)| {
+            let rank_sum: f64 = match &setup.allowed_edge_types {
+                Some(allowed) => self.in_links[i]
+                    .par_iter()
+                    .zip(&self.in_link_types[i])
+                    .filter(|&(_, &edge_type)| allowed[edge_type as usize])
+                    .map(|(&index, _)| p[index] * setup.inverse_out_links[index])
+                    .sum(),
+                None => self.in_links[i]
+                    .par_iter()
+                    .map(|&index| p[index] * setup.inverse_out_links[index])
+                    .sum(),
+            };
+
+            *new_p_i = Self::node_rank(following_prob, rank_sum, dangling_sum, setup.teleport[i]);
+        });
+
+        Self::normalize(new_p);
     }
 
     #[inline]
-    fn calculate_change(p: &[f64], new_p: &[f64]) -> f64 {
+    pub(crate) fn calculate_change(p: &[f64], new_p: &[f64]) -> f64 {
         p.iter()
             .zip(new_p)
             .map(|(&old, &new)| (old - new).abs())
@@ -192,7 +1007,6 @@ impl Pagerank {
     ///
     /// * following_prob - The probability of following a link (damping factor).
     /// * tolerance - The convergence tolerance; computation stops when the change in scores falls below this threshold.
-    /// * result_func - A closure that is called with the node index and its PageRank score after convergence.
     ///
     /// # Examples
     ///
@@ -200,41 +1014,608 @@ impl Pagerank {
     /// let mut pagerank = Pagerank::new(100);
     /// // ... add links ...
     /// let result = pagerank.rank(0.85, 1e-6);
+    ///
+    pub fn rank(&self, following_prob: f64, tolerance: f64) -> Vec<(usize, f64)> {
+        self.rank_with_config(&RankConfig::new(following_prob, tolerance))
+    }
+
+    /// Computes the PageRank scores for all nodes in the graph using the given configuration.
+    ///
+    /// This behaves like rank, additionally applying the rank-time filters of the configuration.
+    /// The result is sorted by descending score.
+    ///
+    /// # Arguments
+    ///
+    /// * config - The parameters of the computation.
+    ///
+    /// # Examples
+    ///
+    /// let mut config = RankConfig::new(0.85, 1e-6);
+    /// config.no_emit.insert(7); // Node 7 is a known bot.
+    /// let result = pagerank.rank_with_config(&config);
+    ///
+    pub fn rank_with_config(&self, config: &RankConfig) -> Vec<(usize, f64)> {
+        Self::install(config, || {
+            self.rank_with_setup(config, &self.setup(config, None))
+        })
+    }
+
+    /// Runs op on a thread pool with config.threads threads, or on the global pool if no thread
+    /// count (or 0) is configured or the pool can't be built.
+    pub(crate) fn install<R, OP>(config: &RankConfig, op: OP) -> R
+    where
+        R: Send,
+        OP: FnOnce() -> R + Send,
+    {
+        match config.threads {
+            None | Some(0) => op(),
+            Some(threads) => match Self::thread_pool(threads) {
+                Some(pool) => pool.install(op),
+                None => op(),
+            },
+        }
+    }
+
+    /// Returns the pool of threads threads, building it on first use. Pools are kept for the
+    /// lifetime of the process, one per distinct thread count, so repeated computations don't
+    /// spawn and join their threads every time.
+    fn thread_pool(threads: usize) -> Option<Arc<rayon::ThreadPool>> {
+        static POOLS: OnceLock<Mutex<HashMap<usize, Arc<rayon::ThreadPool>>>> = OnceLock::new();
+
+        let mut pools = POOLS
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(pool) = pools.get(&threads) {
+            return Some(pool.clone());
+        }
+        let pool = Arc::new(
+            rayon::ThreadPoolBuilder::new()
+                .num_threads(threads)
+                .build()
+                .ok()?,
+        );
+        pools.insert(threads, pool.clone());
+        Some(pool)
+    }
+
+    /// Computes the PageRank scores over the links whose type passes the predicate.
+    ///
+    /// Links whose type is rejected are ignored as if they were not part of the graph, so a
+    /// single stored graph can be ranked under several views. Nodes left without accepted
+    /// outgoing links are treated as dangling nodes. The result is sorted by descending score.
+    ///
+    /// # Arguments
+    ///
+    /// * config - The parameters of the computation.
+    /// * predicate - Returns whether links of the given type are followed.
+    ///
+    /// # Examples
+    ///
+    /// const MENTION: EdgeType = 1;
+    /// let mentions_only = pagerank.rank_filtered(&RankConfig::default(), |edge_type| edge_type == MENTION);
+    ///
+    pub fn rank_filtered<F>(&self, config: &RankConfig, predicate: F) -> Vec<(usize, f64)>
+    where
+        F: Fn(EdgeType) -> bool,
+    {
+        let allowed_edge_types = std::array::from_fn(|edge_type| predicate(edge_type as EdgeType));
+        Self::install(config, || {
+            self.rank_with_setup(config, &self.setup(config, Some(allowed_edge_types)))
+        })
+    }
+
+    /// Computes the scores the iterations start from, according to config.initial.
+    pub(crate) fn initial_scores(&self, config: &RankConfig, setup: &RankSetup) -> Vec<f64> {
+        let size = self.current_available_index;
+        let source = self.initial_source(config);
+        let mut p: Vec<f64> = (0..size)
+            .into_par_iter()
+            .map(|i| self.unnormalized_initial_score(config, source, i))
+            .collect();
+        if let InitialDistribution::WarmStart(previous) = &config.initial {
+            for (key, score) in previous {
+                if let Some(&index) = self.key_to_index.get(key) {
+                    p[index] = *score;
+                }
+            }
+        }
 
-    pub fn rank(&mut self, following_prob: f64, tolerance: f64) -> Vec<(usize, f64)> {
+        let p_sum: f64 = p.par_iter().sum();
+        (0..size)
+            .into_par_iter()
+            .map(|i| Self::normalized_initial_score(config, p[i], p_sum, setup.teleport[i], size))
+            .collect()
+    }
+
+    /// Computes the PageRank scores of the graph with every link reversed.
+    ///
+    /// The same solver runs following the links from their target to their source, without
+    /// materializing the transposed graph. Nodes without incoming links are the dangling nodes
+    /// of the reversed graph. The result is sorted by descending score.
+    ///
+    /// The scores are scattered to the nodes concurrently, so unlike rank_with_config they are
+    /// not bit-reproducible: they can differ in their last bits across runs and thread counts,
+    /// well within the tolerance.
+    ///
+    /// # Arguments
+    ///
+    /// * config - The parameters of the computation.
+    ///
+    /// # Examples
+    ///
+    /// let hubs = pagerank.rank_reversed(&RankConfig::default());
+    ///
+    pub fn rank_reversed(&self, config: &RankConfig) -> Vec<(usize, f64)> {
+        Self::install(config, || {
+            self.rank_with_setup(config, &self.setup_reversed(config))
+        })
+    }
+
+    fn rank_with_setup(&self, config: &RankConfig, setup: &RankSetup) -> Vec<(usize, f64)> {
+        let (p, _, _, _) = self.iterate(config, setup, |_| true);
+        self.emit(config, p).ranked
+    }
+
+    /// Computes the PageRank scores like rank_with_config, reporting the nodes left out of the
+    /// result by config.drop_below.
+    ///
+    /// # Arguments
+    ///
+    /// * config - The parameters of the computation.
+    ///
+    /// # Examples
+    ///
+    /// let mut config = RankConfig::new(0.85, 1e-6);
+    /// config.drop_below = Some(1e-9);
+    /// let result = pagerank.rank_pruned(&config);
+    /// println!("Dropped {} nodes holding {} of the score", result.dropped, result.dropped_mass);
+    ///
+    pub fn rank_pruned(&self, config: &RankConfig) -> PrunedRank {
+        Self::install(config, || {
+            let (p, _, _, _) = self.iterate(config, &self.setup(config, None), |_| true);
+            self.emit(config, p)
+        })
+    }
+
+    /// Pairs the converged scores with their node keys and applies the result options of config.
+    pub(crate) fn emit(&self, config: &RankConfig, p: Vec<f64>) -> PrunedRank {
+        let mut ranked: Vec<(usize, f64)> = p
+            .into_iter()
+            .zip(self.index_to_key.iter())
+            .map(|(p_i, &key)| (key, p_i))
+            .collect();
+        let (dropped, dropped_mass) = config.emit(&mut ranked);
+
+        PrunedRank {
+            ranked,
+            dropped,
+            dropped_mass,
+        }
+    }
+
+    /// Runs the solver selected by config.solver until convergence or until within_budget,
+    /// called with the number of completed iterations before each iteration, returns false.
+    ///
+    /// Returns the scores, the number of iterations, the change of the last iteration and the
+    /// solver that ran, PowerIteration when another one fell back to it.
+    pub(crate) fn iterate<B>(
+        &self,
+        config: &RankConfig,
+        setup: &RankSetup,
+        within_budget: B,
+    ) -> (Vec<f64>, usize, f64, Solver)
+    where
+        B: FnMut(usize) -> bool,
+    {
+        #[cfg(feature = "metrics")]
+        let started = std::time::Instant::now();
+
+        let (p, iterations, change, solver) = self.solve(config, setup, within_budget);
+
+        #[cfg(feature = "metrics")]
+        crate::instrumentation::record_rank(self, iterations, change, started.elapsed());
+
+        (p, iterations, change, solver)
+    }
+
+    /// Runs power iteration, the solver used unless config.solver selects another one.
+    pub(crate) fn power_iterate<B>(
+        &self,
+        config: &RankConfig,
+        setup: &RankSetup,
+        mut within_budget: B,
+    ) -> (Vec<f64>, usize, f64)
+    where
+        B: FnMut(usize) -> bool,
+    {
         let size = self.key_to_index.len();
-        let inverse_of_size = 1.0 / size as f64;
-        let t_over_size = (1.0 - following_prob) * inverse_of_size;
-        let dangling_nodes = self.calculate_dangling_nodes();
 
-        let mut p = vec![inverse_of_size; size]; // Current probabilities
+        let pinned = config.numa_aware && !setup.reversed;
+
+        let mut p = self.initial_scores(config, setup); // Current probabilities
+        if pinned {
+            p = Self::first_touch(&p);
+        }
         let mut new_p = vec![0.0; size]; // Buffer for new probabilities
         let mut change = 2.0;
+        let mut iterations = 0;
 
-        while change > tolerance {
-            self.step(following_prob, t_over_size, &p, &dangling_nodes, &mut new_p);
+        while change > config.tolerance && within_budget(iterations) {
+            if pinned {
+                self.step_pinned(config.following_prob, setup, &p, &mut new_p);
+            } else {
+                self.step(config.following_prob, setup, &p, &mut new_p);
+            }
             change = Self::calculate_change(&p, &new_p);
             std::mem::swap(&mut p, &mut new_p);
+            iterations += 1;
         }
 
+        (p, iterations, change)
+    }
+
+    /// Pairs the scores with their node keys, sorted by descending score.
+    pub(crate) fn ranked(&self, p: Vec<f64>, tie_break: TieBreak) -> Vec<(usize, f64)> {
         let mut ranked: Vec<_> = p
             .into_iter()
-            .enumerate()
-            .map(|(i, p_i)| (*self.index_to_key.get(&i).unwrap(), p_i))
+            .zip(self.index_to_key.iter())
+            .map(|(p_i, &key)| (key, p_i))
             .collect();
 
-        ranked.par_sort_unstable_by(|a, b| {
-            b.1.partial_cmp(&a.1).unwrap_or(std::cmp::Ordering::Equal)
+        tie_break.sort(&mut ranked);
+        ranked
+    }
+
+    fn strength_centrality(&self, strengths: Vec<f64>, normalized: bool) -> Vec<(usize, f64)> {
+        let total: f64 = strengths.par_iter().sum();
+        if normalized && total > 0.0 {
+            let normalized = strengths.into_par_iter().map(|s| s / total).collect();
+            self.ranked(normalized, TieBreak::default())
+        } else {
+            self.ranked(strengths, TieBreak::default())
+        }
+    }
+
+    /// Computes the in-strength centrality of every node, sorted by descending centrality.
+    ///
+    /// The in-strength of a node is the sum of the weights of its incoming links, where the
+    /// weight of a link is the number of times it was added.
+    ///
+    /// # Arguments
+    ///
+    /// * normalized - Whether to divide the strengths by the total weight of the graph, so they
+    ///   sum to 1 like PageRank scores do.
+    ///
+    /// # Examples
+    ///
+    /// let baseline = pagerank.in_strength(true);
+    ///
+    pub fn in_strength(&self, normalized: bool) -> Vec<(usize, f64)> {
+        let strengths = (0..self.current_available_index)
+            .into_par_iter()
+            .map(|i| self.in_links[i].len() as f64)
+            .collect();
+        self.strength_centrality(strengths, normalized)
+    }
+
+    /// Computes the out-strength centrality of every node, sorted by descending centrality.
+    ///
+    /// The out-strength of a node is the sum of the weights of its outgoing links, where the
+    /// weight of a link is the number of times it was added.
+    ///
+    /// # Arguments
+    ///
+    /// * normalized - Whether to divide the strengths by the total weight of the graph, so they
+    ///   sum to 1 like PageRank scores do.
+    ///
+    /// # Examples
+    ///
+    /// let baseline = pagerank.out_strength(true);
+    ///
+    pub fn out_strength(&self, normalized: bool) -> Vec<(usize, f64)> {
+        let strengths = (0..self.current_available_index)
+            .into_par_iter()
+            .map(|i| self.number_out_links[i] as f64)
+            .collect();
+        self.strength_centrality(strengths, normalized)
+    }
+
+    fn undirected_adjacency(&self) -> Vec<Vec<usize>> {
+        let size = self.current_available_index;
+        let mut adjacency: Vec<Vec<usize>> = self.in_links.iter().take(size).cloned().collect();
+        for to in 0..size {
+            for &from in &self.in_links[to] {
+                adjacency[from].push(to);
+            }
+        }
+
+        adjacency.par_iter_mut().for_each(|neighbours| {
+            neighbours.sort_unstable();
+            neighbours.dedup();
         });
+        adjacency
+    }
 
-        ranked
+    fn bfs_order(&self, lower_degree_first: bool) -> Vec<usize> {
+        let size = self.current_available_index;
+        let adjacency = self.undirected_adjacency();
+        let degrees: Vec<usize> = adjacency.iter().map(Vec::len).collect();
+
+        let mut roots: Vec<usize> = (0..size).collect();
+        if lower_degree_first {
+            roots.sort_by_key(|&index| degrees[index]);
+        }
+
+        let mut visited = vec![false; size];
+        let mut order = Vec::with_capacity(size);
+        let mut queue = VecDeque::new();
+        for root in roots {
+            if visited[root] {
+                continue;
+            }
+            visited[root] = true;
+            queue.push_back(root);
+
+            while let Some(index) = queue.pop_front() {
+                order.push(index);
+                let mut neighbours: Vec<usize> = adjacency[index]
+                    .iter()
+                    .copied()
+                    .filter(|&neighbour| !visited[neighbour])
+                    .collect();
+                if lower_degree_first {
+                    neighbours.sort_by_key(|&neighbour| degrees[neighbour]);
+                }
+                for neighbour in neighbours {
+                    visited[neighbour] = true;
+                    queue.push_back(neighbour);
+                }
+            }
+        }
+        order
+    }
+
+    /// Moves the node at index order[i] to index i.
+    fn apply_order(&mut self, order: &[usize]) {
+        let capacity = self.in_links.len();
+        let mut new_indices = vec![0; order.len()];
+        for (new_index, &old_index) in order.iter().enumerate() {
+            new_indices[old_index] = new_index;
+        }
+
+        let mut in_links = vec![Vec::new(); capacity];
+        let mut in_link_types = vec![Vec::new(); capacity];
+        let mut in_link_provenance = self
+            .in_link_provenance
+            .as_ref()
+            .map(|_| vec![Vec::new(); capacity]);
+        let mut number_out_links = vec![0; capacity];
+        for (new_index, &old_index) in order.iter().enumerate() {
+            let tags = self
+                .in_link_provenance
+                .as_ref()
+                .map(|provenance| &provenance[old_index]);
+            // Sorted sources make the gather of each node's in-links read the scores in order.
+            let mut links: Vec<(usize, EdgeType, Provenance)> = self.in_links[old_index]
+                .iter()
+                .zip(&self.in_link_types[old_index])
+                .enumerate()
+                .map(|(position, (&from, &edge_type))| {
+                    let provenance = tags.map_or(NO_PROVENANCE, |tags| tags[position]);
+                    (new_indices[from], edge_type, provenance)
+                })
+                .collect();
+            links.sort_unstable();
+            in_links[new_index] = links.iter().map(|link| link.0).collect();
+            in_link_types[new_index] = links.iter().map(|link| link.1).collect();
+            if let Some(in_link_provenance) = in_link_provenance.as_mut() {
+                in_link_provenance[new_index] = links.iter().map(|link| link.2).collect();
+            }
+            number_out_links[new_index] = self.number_out_links[old_index];
+        }
+
+        let index_to_key: Vec<usize> = order
+            .iter()
+            .map(|&old_index| self.index_to_key[old_index])
+            .collect();
+        let mut key_to_index = ShardedMap::with_capacity(capacity);
+        for (index, &key) in index_to_key.iter().enumerate() {
+            key_to_index.insert(key, index);
+        }
+
+        self.in_links = ChunkedVec::from(in_links);
+        self.in_link_types = ChunkedVec::from(in_link_types);
+        self.in_link_provenance = in_link_provenance.map(ChunkedVec::from);
+        self.number_out_links = ChunkedVec::from(number_out_links);
+        self.key_to_index = key_to_index;
+        self.index_to_key = ChunkedVec::from(index_to_key);
+        self.last_from = None;
+    }
+
+    /// Renumbers the internal node indices to improve the memory locality of rank computations.
+    ///
+    /// Indices are assigned in insertion order, so on large graphs the scores read while
+    /// gathering the in-links of a node are scattered all over memory. Reordering the indices
+    /// once after loading the graph places linked nodes close to each other. The node keys,
+    /// links and scores are not affected, only the speed of the computations.
+    ///
+    /// # Arguments
+    ///
+    /// * order - The ordering to apply.
+    ///
+    /// # Examples
+    ///
+    /// // ... add links ...
+    /// pagerank.reorder(NodeOrder::ReverseCuthillMckee);
+    /// let result = pagerank.rank(0.85, 1e-6);
+    ///
+    pub fn reorder(&mut self, order: NodeOrder) {
+        let size = self.current_available_index;
+        let order = match order {
+            NodeOrder::Degree => {
+                let mut order: Vec<usize> = (0..size).collect();
+                order.par_sort_by_key(|&index| {
+                    std::cmp::Reverse(self.in_links[index].len() + self.number_out_links[index])
+                });
+                order
+            }
+            NodeOrder::Bfs => self.bfs_order(false),
+            NodeOrder::ReverseCuthillMckee => {
+                let mut order = self.bfs_order(true);
+                order.reverse();
+                order
+            }
+        };
+
+        self.apply_order(&order);
+    }
+
+    /// Takes a snapshot of the graph that can be ranked while this instance keeps accepting links.
+    ///
+    /// The snapshot shares the graph data with this instance; while the snapshot is alive, a
+    /// mutation of this instance only copies the chunks of data it touches.
+    ///
+    /// # Examples
+    ///
+    /// let snapshot = pagerank.snapshot();
+    /// let ranker = thread::spawn(move || snapshot.rank(0.85, 1e-6));
+    /// pagerank.link(1, 2).unwrap();
+    ///
+    pub fn snapshot(&self) -> PagerankSnapshot {
+        PagerankSnapshot {
+            graph: self.clone(),
+        }
+    }
+
+    /// Writes the structure of the graph, one node per line, for debugging.
+    ///
+    /// Each line holds the index and key of a node, its number of outgoing links and the
+    /// indices and types of its incoming links. Only the first limit nodes and the first limit
+    /// incoming links of each node are written, so dumping a large graph or a hub by accident
+    /// stays cheap.
+    ///
+    /// # Arguments
+    ///
+    /// * writer - Where the dump is written.
+    /// * limit - The maximum number of nodes, and of incoming links per node, written.
+    ///
+    /// # Errors
+    ///
+    /// Returns the errors of the writer.
+    ///
+    /// # Examples
+    ///
+    /// pagerank.dump(&mut std::io::stderr(), 20)?;
+    ///
+    pub fn dump<W: Write>(&self, writer: &mut W, limit: usize) -> io::Result<()> {
+        writeln!(writer, "{}", self)?;
+        for index in 0..self.size().min(limit) {
+            write!(
+                writer,
+                "{} (key {}): out links {}, in links ",
+                index, self.index_to_key[index], self.number_out_links[index],
+            )?;
+            Self::write_truncated(writer, &self.in_links[index], limit)?;
+            write!(writer, ", in link types ")?;
+            Self::write_truncated(writer, &self.in_link_types[index], limit)?;
+            writeln!(writer)?;
+        }
+        if self.size() > limit {
+            writeln!(writer, "... {} more nodes", self.size() - limit)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the first limit elements of list, followed by the number of elements left out.
+    fn write_truncated<W: Write, T: fmt::Debug>(
+        writer: &mut W,
+        list: &[T],
+        limit: usize,
+    ) -> io::Result<()> {
+        write!(writer, "{:?}", &list[..list.len().min(limit)])?;
+        if list.len() > limit {
+            write!(writer, " (+{} more)", list.len() - limit)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the maximum number of nodes the graph can hold.
+    pub fn capacity(&self) -> usize {
+        self.capacity
+    }
+
+    /// Raises the capacity of the graph by additional nodes, keeping its nodes and links.
+    ///
+    /// # Arguments
+    ///
+    /// * additional - The number of nodes to add to the capacity.
+    ///
+    /// # Errors
+    ///
+    /// Returns a PagerankError if the memory for the new capacity can't be allocated. The graph
+    /// is left unchanged in that case.
+    ///
+    /// # Examples
+    ///
+    /// if let Err(PagerankError::CapacityError(_)) = pagerank.link(from, to) {
+    ///     pagerank.grow_capacity(pagerank.capacity())?;
+    ///     pagerank.link(from, to)?;
+    /// }
+    ///
+    pub fn grow_capacity(&mut self, additional: usize) -> Result<(), PagerankError> {
+        let allocation_error = |e: std::collections::TryReserveError| {
+            let message = format!("Can't grow the capacity by {} nodes: {}", additional, e);
+            PagerankError::AllocationError(message)
+        };
+        let capacity = self.capacity.checked_add(additional).ok_or_else(|| {
+            let message = format!("Can't grow the capacity by {} nodes: overflow", additional);
+            PagerankError::AllocationError(message)
+        })?;
+
+        // Build everything before replacing anything, so a failure leaves the graph unchanged.
+        let in_links = self
+            .in_links
+            .try_resized(capacity, Vec::new())
+            .map_err(allocation_error)?;
+        let in_link_types = self
+            .in_link_types
+            .try_resized(capacity, Vec::new())
+            .map_err(allocation_error)?;
+        let number_out_links = self
+            .number_out_links
+            .try_resized(capacity, 0)
+            .map_err(allocation_error)?;
+        let key_to_index = self
+            .key_to_index
+            .try_resharded(capacity)
+            .map_err(allocation_error)?;
+        let in_link_provenance = match &self.in_link_provenance {
+            Some(provenance) => Some(
+                provenance
+                    .try_resized(capacity, Vec::new())
+                    .map_err(allocation_error)?,
+            ),
+            None => None,
+        };
+
+        self.in_links = in_links;
+        self.in_link_types = in_link_types;
+        self.number_out_links = number_out_links;
+        self.key_to_index = key_to_index;
+        self.in_link_provenance = in_link_provenance;
+        self.capacity = capacity;
+        Ok(())
     }
 
     pub fn clear(&mut self) {
-        self.in_links.iter_mut().for_each(|x| x.clear());
-        self.number_out_links.fill(0);
+        self.in_links.reset(Vec::new(), Vec::clear);
+        self.in_link_types.reset(Vec::new(), Vec::clear);
+        self.in_link_provenance = None;
+        self.number_out_links.reset(0, |count| *count = 0);
         self.current_available_index = 0;
         self.key_to_index.clear();
         self.index_to_key.clear();
+        self.last_from = None;
     }
 }
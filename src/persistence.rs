@@ -0,0 +1,290 @@
+//! This module provides score snapshots, which persist rank results together with a versioned
+//! header describing how they were computed, so a later run can refuse to warm start from scores
+//! computed with parameters that drifted since.
+//!
+//! A snapshot is a text file: a first line with the format version, one header line per field
+//! as a name and a value separated by a space, and one "key score" line per node.
+use crate::config::{InitialDistribution, RankConfig};
+use crate::errors::PagerankError;
+use crate::sharding::mix64;
+use crate::Pagerank;
+use rayon::prelude::*;
+use std::io::{self, BufRead, Lines, Write};
+use std::str::FromStr;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// The version of the snapshot format written by ScoreSnapshot::write. Snapshots with any other
+/// version are refused by ScoreSnapshot::read.
+pub const SNAPSHOT_VERSION: u32 = 1;
+
+const SNAPSHOT_MAGIC: &str = "pagerank_rs score snapshot";
+
+/// A rank result with the description of the computation that produced it.
+///
+/// Fields:
+/// - graph_hash: The Pagerank::graph_hash of the ranked graph, to tell whether the graph changed
+///   since. Warm starting from the scores of a different graph is allowed, it is what warm starts
+///   are for.
+/// - parameters_hash: A hash of the parameters that determine the converged scores:
+///   following_prob, no_teleport and no_emit.
+/// - following_prob: The damping factor of the computation.
+/// - tolerance: The tolerance of the computation.
+/// - timestamp: When the snapshot was taken, in seconds since the Unix epoch.
+/// - scores: The rank result.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoreSnapshot {
+    pub graph_hash: u64,
+    pub parameters_hash: u64,
+    pub following_prob: f64,
+    pub tolerance: f64,
+    pub timestamp: u64,
+    pub scores: Vec<(usize, f64)>,
+}
+
+impl Pagerank {
+    /// Returns a hash of the links of the graph.
+    ///
+    /// The hash only depends on the keys and types of the links, not on the order in which they
+    /// were added, and is stable across runs, platforms and versions of this crate.
+    pub fn graph_hash(&self) -> u64 {
+        let links = (0..self.size())
+            .into_par_iter()
+            .map(|to_as_index| {
+                let to = mix64(self.key_of(to_as_index) as u64);
+                self.in_links_of(to_as_index)
+                    .iter()
+                    .zip(self.in_link_types_of(to_as_index))
+                    .fold(0u64, |hash, (&from_as_index, &edge_type)| {
+                        let from = self.key_of(from_as_index) as u64;
+                        hash.wrapping_add(mix64(mix64(from ^ to) ^ edge_type as u64))
+                    })
+            })
+            .reduce(|| 0, u64::wrapping_add);
+
+        mix64(links ^ self.size() as u64)
+    }
+}
+
+/// Returns a hash of the parameters of config that determine the converged scores.
+fn parameters_hash(config: &RankConfig) -> u64 {
+    let mut no_teleport: Vec<usize> = config.no_teleport.iter().copied().collect();
+    let mut no_emit: Vec<usize> = config.no_emit.iter().copied().collect();
+    no_teleport.sort_unstable();
+    no_emit.sort_unstable();
+
+    let mut hash = mix64(config.following_prob.to_bits());
+    for keys in [no_teleport, no_emit] {
+        hash = mix64(hash ^ keys.len() as u64);
+        for key in keys {
+            hash = mix64(hash ^ key as u64);
+        }
+    }
+    hash
+}
+
+impl ScoreSnapshot {
+    /// Constructs a snapshot of scores computed on pagerank with config, taken now.
+    ///
+    /// # Arguments
+    ///
+    /// * pagerank - The ranked graph.
+    /// * config - The parameters of the computation.
+    /// * scores - The rank result.
+    ///
+    /// # Examples
+    ///
+    /// let snapshot = ScoreSnapshot::new(&pagerank, &config, pagerank.rank_with_config(&config));
+    ///
+    pub fn new(pagerank: &Pagerank, config: &RankConfig, scores: Vec<(usize, f64)>) -> Self {
+        ScoreSnapshot {
+            graph_hash: pagerank.graph_hash(),
+            parameters_hash: parameters_hash(config),
+            following_prob: config.following_prob,
+            tolerance: config.tolerance,
+            timestamp: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs()),
+            scores,
+        }
+    }
+
+    /// Writes the snapshot in the current format version.
+    ///
+    /// # Arguments
+    ///
+    /// * writer - Where the snapshot is written, e.g. a BufWriter over a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns a PagerankError if writing fails.
+    ///
+    /// # Examples
+    ///
+    /// snapshot.write(BufWriter::new(File::create("scores.snapshot")?))?;
+    ///
+    pub fn write<W: Write>(&self, mut writer: W) -> Result<(), PagerankError> {
+        let io_error = |e: io::Error| PagerankError::IoError(e.to_string());
+
+        writeln!(writer, "{} {}", SNAPSHOT_MAGIC, SNAPSHOT_VERSION).map_err(io_error)?;
+        writeln!(writer, "graph_hash {:016x}", self.graph_hash).map_err(io_error)?;
+        writeln!(writer, "parameters_hash {:016x}", self.parameters_hash).map_err(io_error)?;
+        writeln!(writer, "following_prob {}", self.following_prob).map_err(io_error)?;
+        writeln!(writer, "tolerance {}", self.tolerance).map_err(io_error)?;
+        writeln!(writer, "timestamp {}", self.timestamp).map_err(io_error)?;
+        writeln!(writer, "scores {}", self.scores.len()).map_err(io_error)?;
+        for (key, score) in &self.scores {
+            writeln!(writer, "{} {}", key, score).map_err(io_error)?;
+        }
+
+        writer.flush().map_err(io_error)
+    }
+
+    /// Reads a snapshot written by ScoreSnapshot::write.
+    ///
+    /// # Arguments
+    ///
+    /// * reader - The snapshot, e.g. a BufReader over a file.
+    ///
+    /// # Errors
+    ///
+    /// Returns a SnapshotError if the snapshot was written in another format version than
+    /// SNAPSHOT_VERSION, and a PagerankError if it is malformed or reading fails.
+    ///
+    /// # Examples
+    ///
+    /// let snapshot = ScoreSnapshot::read(BufReader::new(File::open("scores.snapshot")?))?;
+    ///
+    pub fn read<R: BufRead>(reader: R) -> Result<Self, PagerankError> {
+        let mut reader = SnapshotReader {
+            lines: reader.lines(),
+            line: 0,
+        };
+
+        let first = reader.next_line()?;
+        let version = first
+            .strip_prefix(SNAPSHOT_MAGIC)
+            .ok_or_else(|| reader.error("Not a score snapshot"))?;
+        let version: u32 = reader.parse(version.trim())?;
+        if version != SNAPSHOT_VERSION {
+            return Err(PagerankError::SnapshotError(format!(
+                "Unsupported snapshot version {}, the supported version is {}",
+                version, SNAPSHOT_VERSION
+            )));
+        }
+
+        let graph_hash = reader.hex_field("graph_hash")?;
+        let parameters_hash = reader.hex_field("parameters_hash")?;
+        let following_prob = reader.field("following_prob")?;
+        let tolerance = reader.field("tolerance")?;
+        let timestamp = reader.field("timestamp")?;
+        let count: usize = reader.field("scores")?;
+
+        let mut scores = Vec::new();
+        for _ in 0..count {
+            let line = reader.next_line()?;
+            let (key, score) = line
+                .split_once(' ')
+                .ok_or_else(|| reader.error("Expected a key and a score"))?;
+            scores.push((reader.parse(key)?, reader.parse(score)?));
+        }
+
+        Ok(ScoreSnapshot {
+            graph_hash,
+            parameters_hash,
+            following_prob,
+            tolerance,
+            timestamp,
+            scores,
+        })
+    }
+
+    /// Returns whether the snapshot was computed with the same following_prob, no_teleport and
+    /// no_emit as config, so its scores converge to the scores config computes.
+    pub fn is_compatible(&self, config: &RankConfig) -> bool {
+        self.parameters_hash == parameters_hash(config)
+    }
+
+    /// Sets config.initial to start from the scores of the snapshot.
+    ///
+    /// # Arguments
+    ///
+    /// * config - The parameters of the next computation.
+    ///
+    /// # Errors
+    ///
+    /// Returns a SnapshotError, leaving config unchanged, if the snapshot is not compatible with
+    /// config.
+    ///
+    /// # Examples
+    ///
+    /// let mut config = RankConfig::new(0.85, 1e-6);
+    /// if let Err(error) = snapshot.warm_start(&mut config) {
+    ///     log::warn!("Ranking from scratch: {}", error);
+    /// }
+    ///
+    pub fn warm_start(&self, config: &mut RankConfig) -> Result<(), PagerankError> {
+        if !self.is_compatible(config) {
+            let reason = if self.following_prob.to_bits() != config.following_prob.to_bits() {
+                format!(
+                    "following_prob {} instead of {}",
+                    self.following_prob, config.following_prob
+                )
+            } else {
+                "different no_teleport or no_emit keys".to_string()
+            };
+            return Err(PagerankError::SnapshotError(format!(
+                "Can't warm start from a snapshot computed with {}",
+                reason
+            )));
+        }
+
+        config.initial = InitialDistribution::WarmStart(self.scores.clone());
+        Ok(())
+    }
+}
+
+/// Reads the lines of a snapshot, keeping track of the line number for error messages.
+struct SnapshotReader<R> {
+    lines: Lines<R>,
+    line: usize,
+}
+
+impl<R: BufRead> SnapshotReader<R> {
+    fn error(&self, message: &str) -> PagerankError {
+        PagerankError::ParseError(format!("Line {}: {}", self.line, message))
+    }
+
+    fn next_line(&mut self) -> Result<String, PagerankError> {
+        self.line += 1;
+        match self.lines.next() {
+            Some(Ok(line)) => Ok(line),
+            Some(Err(e)) => Err(PagerankError::IoError(e.to_string())),
+            None => Err(self.error("Unexpected end of the snapshot")),
+        }
+    }
+
+    fn parse<T: FromStr>(&self, value: &str) -> Result<T, PagerankError> {
+        value
+            .parse()
+            .map_err(|_| self.error(&format!("Invalid value '{}'", value)))
+    }
+
+    fn value_of(&mut self, name: &str) -> Result<String, PagerankError> {
+        let line = self.next_line()?;
+        match line.split_once(' ') {
+            Some((found, value)) if found == name => Ok(value.to_string()),
+            _ => Err(self.error(&format!("Expected {}", name))),
+        }
+    }
+
+    fn field<T: FromStr>(&mut self, name: &str) -> Result<T, PagerankError> {
+        let value = self.value_of(name)?;
+        self.parse(&value)
+    }
+
+    fn hex_field(&mut self, name: &str) -> Result<u64, PagerankError> {
+        let value = self.value_of(name)?;
+        u64::from_str_radix(&value, 16)
+            .map_err(|_| self.error(&format!("Invalid value '{}'", value)))
+    }
+}
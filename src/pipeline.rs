@@ -0,0 +1,101 @@
+//! This module provides a double-buffered pipeline around Pagerank, allowing new links to be
+//! ingested into a staging graph while a previously captured snapshot of the graph is ranked,
+//! typically on another thread.
+//!
+//! Ingestion never waits for the duration of a rank computation, only for the swap of the
+//! staging graph into a snapshot and for the links ingested by other threads. Snapshots share
+//! the graph data with the staging graph in chunks of CHUNK_SIZE nodes, so while a snapshot is
+//! alive each link only copies the chunks it touches: at most the chunks of its two nodes and
+//! one shard of the key index, never the whole graph.
+//!
+//! These copies are made under the lock of the staging graph. The first link touching a chunk
+//! after a swap copies the incoming links of all the nodes of the chunk, so with hubs in the
+//! chunk it can hold the lock for as long as copying millions of links.
+use crate::errors::PagerankError;
+use crate::{Pagerank, PagerankSnapshot};
+use std::sync::{Mutex, MutexGuard};
+
+/// A thread-safe pipeline that accumulates links into a staging graph and hands out snapshots
+/// of it for ranking.
+///
+/// The pipeline can be shared between threads (e.g. behind an Arc): ingestion threads call
+/// link while a ranking thread periodically calls swap and ranks the returned graph.
+///
+/// Fields:
+/// - staging: The graph receiving new links.
+#[derive(Debug)]
+pub struct PagerankPipeline {
+    staging: Mutex<Pagerank>,
+}
+
+impl PagerankPipeline {
+    /// Constructs a new pipeline whose staging graph has the specified capacity.
+    ///
+    /// # Arguments
+    ///
+    /// * capacity - The maximum number of nodes in the graph.
+    ///
+    /// # Examples
+    ///
+    /// let pipeline = PagerankPipeline::new(100);
+    ///
+    pub fn new(capacity: usize) -> PagerankPipeline {
+        PagerankPipeline {
+            staging: Mutex::new(Pagerank::new(capacity)),
+        }
+    }
+
+    fn staging(&self) -> MutexGuard<'_, Pagerank> {
+        self.staging
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Adds a directed link from the from node to the to node in the staging graph.
+    ///
+    /// The first link touching a chunk of nodes shared with a snapshot copies the chunk, see
+    /// the module documentation.
+    ///
+    /// # Errors
+    ///
+    /// Returns a PagerankError if adding the link would exceed the graph's capacity.
+    ///
+    /// # Examples
+    ///
+    /// let pipeline = PagerankPipeline::new(100);
+    /// pipeline.link(1, 2).unwrap();
+    ///
+    pub fn link(&self, from: usize, to: usize) -> Result<(), PagerankError> {
+        self.staging().link(from, to)
+    }
+
+    /// Atomically captures the staging graph, returning a graph that can be ranked while
+    /// ingestion continues.
+    ///
+    /// Links added after the swap are not visible in the returned graph but are kept in the
+    /// staging graph, so the next swap contains every link ingested so far.
+    ///
+    /// The swap itself only copies the chunk pointers of the staging graph, so the lock is held
+    /// for a time proportional to the number of chunks rather than to the number of links.
+    ///
+    /// # Examples
+    ///
+    /// let pipeline = Arc::new(PagerankPipeline::new(100));
+    /// let snapshot = pipeline.swap();
+    /// let ranker = thread::spawn(move || snapshot.rank(0.85, 1e-6));
+    /// pipeline.link(1, 2).unwrap(); // Does not wait for the ranker thread.
+    ///
+    pub fn swap(&self) -> PagerankSnapshot {
+        self.staging().snapshot()
+    }
+
+    /// Swaps out the staging graph and ranks it, without blocking ingestion while ranking.
+    ///
+    /// # Arguments
+    ///
+    /// * following_prob - The probability of following a link (damping factor).
+    /// * tolerance - The convergence tolerance.
+    pub fn rank(&self, following_prob: f64, tolerance: f64) -> Vec<(usize, f64)> {
+        self.swap().rank(following_prob, tolerance)
+    }
+}
@@ -0,0 +1,119 @@
+//! This module provides the ScorePublisher struct, which notifies subscribers of the nodes whose
+//! score changed significantly between two rank computations, so consumers such as caches can
+//! react to the changes without diffing full results themselves.
+use std::collections::HashMap;
+
+/// The change of a node's score between two published results.
+///
+/// Fields:
+/// - key: The node identifier.
+/// - previous: The score in the previously published result, 0 if the node was not part of it.
+/// - current: The score in the latest published result, 0 if the node is no longer part of it.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ScoreDelta {
+    pub key: usize,
+    pub previous: f64,
+    pub current: f64,
+}
+
+impl ScoreDelta {
+    /// Returns the absolute change of the score.
+    pub fn change(&self) -> f64 {
+        (self.current - self.previous).abs()
+    }
+}
+
+type Callback = Box<dyn FnMut(&[ScoreDelta]) + Send>;
+
+struct Subscriber {
+    threshold: f64,
+    callback: Callback,
+}
+
+/// Publishes the score changes of successive rank results to subscribers.
+///
+/// Each subscriber registers a threshold and is called after every publish with the nodes whose
+/// score changed by more than that threshold, if there are any.
+///
+/// Fields:
+/// - previous: The scores of the last published result, by node identifier.
+/// - subscribers: The registered subscribers.
+#[derive(Default)]
+pub struct ScorePublisher {
+    previous: HashMap<usize, f64>,
+    subscribers: Vec<Subscriber>,
+}
+
+impl ScorePublisher {
+    /// Constructs a new ScorePublisher without subscribers nor previous scores.
+    pub fn new() -> ScorePublisher {
+        ScorePublisher::default()
+    }
+
+    /// Registers a callback receiving the nodes whose score changed by more than threshold.
+    ///
+    /// The deltas passed to the callback are sorted by descending change.
+    ///
+    /// # Examples
+    ///
+    /// let mut publisher = ScorePublisher::new();
+    /// publisher.subscribe(0.001, |deltas| {
+    ///     deltas.iter().for_each(|delta| cache.invalidate(delta.key));
+    /// });
+    ///
+    pub fn subscribe<F>(&mut self, threshold: f64, callback: F)
+    where
+        F: FnMut(&[ScoreDelta]) + Send + 'static,
+    {
+        self.subscribers.push(Subscriber {
+            threshold,
+            callback: Box::new(callback),
+        });
+    }
+
+    /// Compares a new rank result with the previously published one and notifies subscribers.
+    ///
+    /// The first published result is compared with an empty result, so every node with a score
+    /// above a subscriber's threshold is reported.
+    ///
+    /// # Arguments
+    ///
+    /// * ranked - The result of a rank computation.
+    ///
+    /// # Examples
+    ///
+    /// publisher.publish(&pagerank.rank(0.85, 1e-6));
+    ///
+    pub fn publish(&mut self, ranked: &[(usize, f64)]) {
+        let current: HashMap<usize, f64> = ranked.iter().copied().collect();
+
+        let mut deltas: Vec<ScoreDelta> = current
+            .iter()
+            .map(|(&key, &score)| ScoreDelta {
+                key,
+                previous: self.previous.get(&key).copied().unwrap_or(0.0),
+                current: score,
+            })
+            .chain(
+                self.previous
+                    .iter()
+                    .filter(|(key, _)| !current.contains_key(key))
+                    .map(|(&key, &score)| ScoreDelta {
+                        key,
+                        previous: score,
+                        current: 0.0,
+                    }),
+            )
+            .collect();
+        deltas.sort_unstable_by(|a, b| b.change().total_cmp(&a.change()));
+
+        for subscriber in &mut self.subscribers {
+            let changed = deltas.partition_point(|delta| delta.change() > subscriber.threshold);
+            if changed > 0 {
+                (subscriber.callback)(&deltas[..changed]);
+            }
+        }
+
+        self.previous = current;
+    }
+}
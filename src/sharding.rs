@@ -0,0 +1,277 @@
+//! This module provides the minimal hooks needed to rank a graph partitioned into shards, each
+//! ranked on its own thread or machine.
+//!
+//! Nodes are assigned to shards by hashing their keys with shard_of. Every shard stores the
+//! outgoing links of the nodes it owns and, once per iteration, sends the score flowing through
+//! links to nodes owned by other shards. The transport (channels, network, ...) is provided by
+//! the user through the ShardExchange trait.
+use crate::config::RankConfig;
+use crate::errors::PagerankError;
+use crate::Pagerank;
+use rayon::prelude::*;
+
+/// Returns the shard owning key when the key space is partitioned into shard_count shards.
+///
+/// The assignment only depends on its arguments, so it is stable across runs, machines and
+/// platforms.
+///
+/// # Panics
+///
+/// Panics if shard_count is 0.
+///
+/// # Examples
+///
+/// let shard = shard_of(42, 4);
+/// shards[shard].link(42, 7).unwrap();
+///
+pub fn shard_of(key: usize, shard_count: usize) -> usize {
+    (mix64(key as u64) % shard_count as u64) as usize
+}
+
+/// The SplitMix64 finalizer, unlike std's hashers it is guaranteed to never change.
+pub(crate) fn mix64(mut x: u64) -> u64 {
+    x = (x ^ (x >> 30)).wrapping_mul(0xbf58476d1ce4e5b9);
+    x = (x ^ (x >> 27)).wrapping_mul(0x94d049bb133111eb);
+    x ^ (x >> 31)
+}
+
+/// Communication between the shards of a partitioned PageRank computation.
+///
+/// All the shards call the methods of their exchange in the same order, so each call is a
+/// synchronization point: it must only return once every shard made the matching call.
+pub trait ShardExchange {
+    /// Sends outgoing[shard] to each other shard and returns the entries every other shard sent
+    /// to the calling shard. The entries are (key, score) pairs.
+    ///
+    /// outgoing has one entry per shard, the entry of the calling shard is always empty.
+    fn exchange(&mut self, outgoing: Vec<Vec<(usize, f64)>>) -> Vec<(usize, f64)>;
+
+    /// Returns the sum of value over all the shards.
+    fn sum(&mut self, value: f64) -> f64;
+}
+
+/// One shard of a graph partitioned by key hash.
+///
+/// A shard stores the outgoing links of the nodes it owns. Link targets owned by other shards
+/// are kept as local placeholders that forward their incoming score to their owner.
+///
+/// Fields:
+/// - graph: The links whose source is owned by this shard.
+/// - shard: The index of this shard.
+/// - shard_count: The total number of shards.
+#[derive(Debug, Clone)]
+pub struct PagerankShard {
+    graph: Pagerank,
+    shard: usize,
+    shard_count: usize,
+}
+
+impl PagerankShard {
+    /// Constructs an empty shard.
+    ///
+    /// # Arguments
+    ///
+    /// * shard - The index of this shard, in 0..shard_count.
+    /// * shard_count - The total number of shards.
+    /// * capacity - The maximum number of nodes stored by this shard, including the
+    ///   placeholders of link targets owned by other shards.
+    ///
+    /// # Panics
+    ///
+    /// Panics if shard is not lower than shard_count.
+    pub fn new(shard: usize, shard_count: usize, capacity: usize) -> PagerankShard {
+        assert!(shard < shard_count, "shard must be lower than shard_count");
+
+        PagerankShard {
+            graph: Pagerank::new(capacity),
+            shard,
+            shard_count,
+        }
+    }
+
+    /// Returns whether key is owned by this shard.
+    pub fn owns(&self, key: usize) -> bool {
+        shard_of(key, self.shard_count) == self.shard
+    }
+
+    fn check_owned(&self, key: usize) -> Result<(), PagerankError> {
+        if self.owns(key) {
+            return Ok(());
+        }
+
+        let message = format!(
+            "Node {} belongs to shard {}, not to shard {}",
+            key,
+            shard_of(key, self.shard_count),
+            self.shard,
+        );
+        Err(PagerankError::ShardError(message))
+    }
+
+    /// Adds a node owned by this shard without links.
+    ///
+    /// Nodes that are only the target of links stored in other shards are discovered when
+    /// ranking, so this is only needed for nodes without any link.
+    ///
+    /// # Errors
+    ///
+    /// Returns a PagerankError if the node is not owned by this shard or adding it would exceed
+    /// the shard's capacity.
+    pub fn add_node(&mut self, key: usize) -> Result<(), PagerankError> {
+        self.check_owned(key)?;
+        self.graph.key_as_array_index(key)?;
+        Ok(())
+    }
+
+    /// Adds a directed link from the from node, which must be owned by this shard, to the to
+    /// node, which can be owned by any shard.
+    ///
+    /// # Errors
+    ///
+    /// Returns a PagerankError if the from node is not owned by this shard or adding the link
+    /// would exceed the shard's capacity.
+    ///
+    /// # Examples
+    ///
+    /// let mut shard = PagerankShard::new(shard_of(1, 4), 4, 100);
+    /// shard.link(1, 2).unwrap();
+    ///
+    pub fn link(&mut self, from: usize, to: usize) -> Result<(), PagerankError> {
+        self.check_owned(from)?;
+        self.graph.link(from, to)
+    }
+
+    fn owners(&self) -> Vec<usize> {
+        (0..self.graph.size())
+            .into_par_iter()
+            .map(|index| shard_of(self.graph.key_of(index), self.shard_count))
+            .collect()
+    }
+
+    /// Tells the owners of the link targets stored as placeholders about them, so every shard
+    /// knows all the nodes it owns.
+    fn discover_nodes<E: ShardExchange>(&mut self, exchange: &mut E) -> Result<(), PagerankError> {
+        let mut outgoing = vec![Vec::new(); self.shard_count];
+        for (index, owner) in self.owners().into_iter().enumerate() {
+            if owner != self.shard {
+                outgoing[owner].push((self.graph.key_of(index), 0.0));
+            }
+        }
+
+        for (key, _) in exchange.exchange(outgoing) {
+            self.graph.key_as_array_index(key)?;
+        }
+        Ok(())
+    }
+
+    /// Computes the PageRank scores of the nodes owned by this shard.
+    ///
+    /// Every shard must call rank at the same time with the same configuration, each with its
+    /// own exchange. Only following_prob, tolerance, the score bounds, tie_break and drop_below
+    /// are taken from the configuration, the rank-time filters and initial distribution are not
+    /// supported on partitioned graphs.
+    /// The result is sorted by descending score, and the scores of all the shards together sum
+    /// to 1.
+    ///
+    /// # Arguments
+    ///
+    /// * config - The parameters of the computation.
+    /// * exchange - The communication channel with the other shards.
+    ///
+    /// # Errors
+    ///
+    /// Returns a PagerankError if registering the nodes discovered through other shards would
+    /// exceed the shard's capacity. The exchange is responsible for aborting the other shards.
+    ///
+    /// # Examples
+    ///
+    /// // On every shard's thread or machine:
+    /// let result = shard.rank(&RankConfig::default(), &mut exchange).unwrap();
+    ///
+    pub fn rank<E: ShardExchange>(
+        &mut self,
+        config: &RankConfig,
+        exchange: &mut E,
+    ) -> Result<Vec<(usize, f64)>, PagerankError> {
+        self.discover_nodes(exchange)?;
+
+        let graph = &self.graph;
+        let size = graph.size();
+        let owners = self.owners();
+        let owned: Vec<bool> = owners.iter().map(|&owner| owner == self.shard).collect();
+        let owned_count = owned.iter().filter(|&&is_owned| is_owned).count();
+        let total = exchange.sum(owned_count as f64);
+        if total == 0.0 {
+            return Ok(Vec::new());
+        }
+        let inverse_of_total = 1.0 / total;
+
+        let mut p: Vec<f64> = owned
+            .iter()
+            .map(|&is_owned| if is_owned { inverse_of_total } else { 0.0 })
+            .collect();
+        let mut change = 2.0;
+
+        while change > config.tolerance {
+            let local_dangling_sum: f64 = (0..size)
+                .filter(|&index| owned[index] && graph.number_out_links_of(index) == 0)
+                .map(|index| p[index])
+                .sum();
+            let dangling_sum = exchange.sum(local_dangling_sum);
+
+            let mut rank_sums: Vec<f64> = (0..size)
+                .into_par_iter()
+                .map(|index| {
+                    graph
+                        .in_links_of(index)
+                        .iter()
+                        .map(|&from| p[from] / graph.number_out_links_of(from) as f64)
+                        .sum()
+                })
+                .collect();
+
+            let mut outgoing = vec![Vec::new(); self.shard_count];
+            for index in 0..size {
+                if !owned[index] && rank_sums[index] != 0.0 {
+                    outgoing[owners[index]].push((graph.key_of(index), rank_sums[index]));
+                }
+            }
+            for (key, score) in exchange.exchange(outgoing) {
+                if let Some(index) = graph.index_of(key) {
+                    rank_sums[index] += score;
+                }
+            }
+
+            let mut new_p: Vec<f64> = (0..size)
+                .map(|index| {
+                    if owned[index] {
+                        Pagerank::node_rank(
+                            config.following_prob,
+                            rank_sums[index],
+                            dangling_sum,
+                            inverse_of_total,
+                        )
+                    } else {
+                        0.0
+                    }
+                })
+                .collect();
+
+            let v_sum = exchange.sum(new_p.iter().sum());
+            new_p.iter_mut().for_each(|x| *x /= v_sum);
+            change = exchange.sum(Pagerank::calculate_change(&p, &new_p));
+            p = new_p;
+        }
+
+        let mut ranked: Vec<_> = p
+            .into_iter()
+            .enumerate()
+            .filter(|&(index, _)| owned[index])
+            .map(|(index, p_i)| (graph.key_of(index), p_i))
+            .collect();
+
+        config.emit(&mut ranked);
+
+        Ok(ranked)
+    }
+}
@@ -0,0 +1,385 @@
+//! This module provides the solvers that can replace power iteration and the heuristic behind
+//! Solver::Auto, which picks one of them from the size, density and strongly connected
+//! components of the graph so users get good performance without comparing solvers themselves.
+use crate::config::{RankConfig, Solver, EXACT_MAX_NODES};
+use crate::pagerank::RankSetup;
+use crate::Pagerank;
+use rayon::prelude::*;
+
+/// The largest number of nodes Solver::Auto solves exactly, which bounds the matrix to 32 MB.
+const AUTO_EXACT_MAX_NODES: usize = 2_000;
+/// How many elimination steps Solver::Auto assumes to take the time of a link visit, as dense
+/// rows are traversed contiguously while links are gathered from anywhere in the score vector.
+const ELIMINATION_STEPS_PER_VISIT: f64 = 16.0;
+/// The largest number of threads on which Solver::Auto prefers sequential Gauss-Seidel.
+const AUTO_GAUSS_SEIDEL_MAX_THREADS: usize = 2;
+
+impl Pagerank {
+    /// Returns the solver Solver::Auto uses to rank the graph with config.
+    ///
+    /// # Arguments
+    ///
+    /// * config - The parameters of the computation.
+    ///
+    /// # Examples
+    ///
+    /// let mut config = RankConfig::new(0.85, 1e-6);
+    /// config.solver = Solver::Auto;
+    /// log::debug!("Ranking with {:?}", pagerank.auto_solver(&config));
+    ///
+    pub fn auto_solver(&self, config: &RankConfig) -> Solver {
+        Self::install(config, || {
+            let setup = self.setup(config, None);
+            if !self.solves_linear_system(config, &setup) {
+                return Solver::PowerIteration;
+            }
+            self.choose_solver(config, &setup).0
+        })
+    }
+
+    /// Runs the solver selected by config.solver, see Pagerank::iterate.
+    pub(crate) fn solve<B>(
+        &self,
+        config: &RankConfig,
+        setup: &RankSetup,
+        mut within_budget: B,
+    ) -> (Vec<f64>, usize, f64, Solver)
+    where
+        B: FnMut(usize) -> bool,
+    {
+        let (solver, components) = match config.solver {
+            _ if !self.solves_linear_system(config, setup) => (Solver::PowerIteration, None),
+            Solver::Auto => self.choose_solver(config, setup),
+            solver => (solver, None),
+        };
+
+        if matches!(solver, Solver::Condensation | Solver::Exact) && !within_budget(0) {
+            return (self.initial_scores(config, setup), 0, 2.0, solver);
+        }
+
+        let ((p, iterations, change), solver) = match solver {
+            Solver::GaussSeidel => (self.gauss_seidel(config, setup, within_budget), solver),
+            Solver::Condensation => {
+                let components = components.unwrap_or_else(|| self.components(setup));
+                (self.condensation(config, setup, &components), solver)
+            }
+            Solver::Exact => match self.exact(config.following_prob, setup) {
+                Some(p) => ((p, 1, 0.0), solver),
+                None => (
+                    self.power_iterate(config, setup, within_budget),
+                    Solver::PowerIteration,
+                ),
+            },
+            Solver::PowerIteration | Solver::Auto => (
+                self.power_iterate(config, setup, within_budget),
+                Solver::PowerIteration,
+            ),
+        };
+        (p, iterations, change, solver)
+    }
+
+    /// Returns an upper bound of the sum of the absolute differences between the scores p, as
+    /// returned by solve with solver, and the converged scores.
+    ///
+    /// Power iteration contracts by following_prob, so the distance is at most following_prob /
+    /// (1 - following_prob) times the change of its last iteration. The other iterative solvers
+    /// don't contract the same way, their bound is derived from the change of one more power
+    /// iteration step over p instead, which costs an iteration. Exact solves have no error.
+    pub(crate) fn error_bound(
+        &self,
+        config: &RankConfig,
+        setup: &RankSetup,
+        solver: Solver,
+        p: &[f64],
+        iterations: usize,
+        change: f64,
+    ) -> f64 {
+        let following_prob = config.following_prob;
+        if following_prob >= 1.0 {
+            return 2.0;
+        }
+
+        let bound = match solver {
+            Solver::Exact if iterations > 0 => 0.0,
+            Solver::GaussSeidel | Solver::Condensation if iterations > 0 => {
+                // p is at most residual / (1 - following_prob) away from the fixed point of
+                // the step, as the step contracts by following_prob.
+                let mut next = vec![0.0; p.len()];
+                self.step(following_prob, setup, p, &mut next);
+                Self::calculate_change(p, &next) / (1.0 - following_prob)
+            }
+            _ => change * following_prob / (1.0 - following_prob),
+        };
+        bound.min(2.0)
+    }
+
+    /// Returns whether the scores are the normalized solution of the linear system solved by the
+    /// alternatives to power iteration, which needs some score to teleport.
+    fn solves_linear_system(&self, config: &RankConfig, setup: &RankSetup) -> bool {
+        !setup.reversed && config.following_prob < 1.0 && self.size() > 0
+    }
+
+    /// Picks the solver of Solver::Auto, returning the strongly connected components too when
+    /// they were computed to pick Condensation.
+    fn choose_solver(
+        &self,
+        config: &RankConfig,
+        setup: &RankSetup,
+    ) -> (Solver, Option<Vec<Vec<usize>>>) {
+        if config.numa_aware {
+            return (Solver::PowerIteration, None);
+        }
+
+        // Power iteration contracts by following_prob, which predicts its number of iterations.
+        let iterations = (config.tolerance.ln() / config.following_prob.ln()).ceil();
+        let iterations = if iterations.is_nan() {
+            1.0
+        } else {
+            iterations.clamp(1.0, 1e6)
+        };
+        let size = self.size();
+        let elimination_steps = (size as f64).powi(3) / 3.0;
+        let iteration_visits = self.visits_per_iteration() as f64 * iterations;
+        if size <= AUTO_EXACT_MAX_NODES
+            && elimination_steps <= ELIMINATION_STEPS_PER_VISIT * iteration_visits
+        {
+            return (Solver::Exact, None);
+        }
+
+        let components = self.components(setup);
+        let largest = components.iter().map(Vec::len).max().unwrap_or(0);
+        if largest * 2 <= size {
+            return (Solver::Condensation, Some(components));
+        }
+
+        if rayon::current_num_threads() <= AUTO_GAUSS_SEIDEL_MAX_THREADS {
+            (Solver::GaussSeidel, None)
+        } else {
+            (Solver::PowerIteration, None)
+        }
+    }
+
+    /// Updates the unnormalized scores x of nodes in place, in order, returning the sum of the
+    /// absolute changes.
+    fn sweep<I>(&self, following_prob: f64, setup: &RankSetup, x: &mut [f64], nodes: I) -> f64
+    where
+        I: IntoIterator<Item = usize>,
+    {
+        let mut change = 0.0;
+        for i in nodes {
+            let rank_sum: f64 = self
+                .score_links(setup, i)
+                .map(|(index, share)| x[index] * share)
+                .sum();
+            let x_i = Self::node_rank(following_prob, rank_sum, 0.0, setup.teleport[i]);
+            change += (x_i - x[i]).abs();
+            x[i] = x_i;
+        }
+        change
+    }
+
+    fn gauss_seidel<B>(
+        &self,
+        config: &RankConfig,
+        setup: &RankSetup,
+        mut within_budget: B,
+    ) -> (Vec<f64>, usize, f64)
+    where
+        B: FnMut(usize) -> bool,
+    {
+        let mut x = self.initial_scores(config, setup);
+        let mut change = 2.0;
+        let mut iterations = 0;
+
+        while change > config.tolerance && within_budget(iterations) {
+            change = self.sweep(config.following_prob, setup, &mut x, 0..self.size());
+            iterations += 1;
+        }
+
+        Self::normalize(&mut x);
+        (x, iterations, change)
+    }
+
+    /// Solves components in order with Gauss-Seidel. Each component is swept until its change
+    /// falls below its share of the tolerance, so the changes add up to at most the tolerance.
+    ///
+    /// Returns the scores, the largest number of sweeps of a component and the sum of the last
+    /// changes of the components.
+    fn condensation(
+        &self,
+        config: &RankConfig,
+        setup: &RankSetup,
+        components: &[Vec<usize>],
+    ) -> (Vec<f64>, usize, f64) {
+        let size = self.size();
+        let mut x = self.initial_scores(config, setup);
+        let mut iterations = 0;
+        let mut change = 0.0;
+
+        for component in components {
+            let nodes = || component.iter().copied();
+            let first = component[0];
+            let cyclic = component.len() > 1
+                || self
+                    .score_links(setup, first)
+                    .any(|(index, _)| index == first);
+
+            // A node without a link to itself only depends on earlier components.
+            if !cyclic {
+                self.sweep(config.following_prob, setup, &mut x, nodes());
+                iterations = iterations.max(1);
+                continue;
+            }
+
+            let tolerance = config.tolerance * component.len() as f64 / size as f64;
+            let mut component_change = 2.0;
+            let mut sweeps = 0;
+            while component_change > tolerance {
+                component_change = self.sweep(config.following_prob, setup, &mut x, nodes());
+                sweeps += 1;
+            }
+            iterations = iterations.max(sweeps);
+            change += component_change;
+        }
+
+        Self::normalize(&mut x);
+        (x, iterations, change)
+    }
+
+    /// Returns the strongly connected components of the links carrying score, in topological
+    /// order: links between components always go from an earlier to a later component.
+    fn components(&self, setup: &RankSetup) -> Vec<Vec<usize>> {
+        const UNVISITED: usize = usize::MAX;
+        let size = self.size();
+        let mut order = vec![UNVISITED; size];
+        let mut low = vec![0; size];
+        let mut on_stack = vec![false; size];
+        let mut stack = Vec::new();
+        let mut components = Vec::new();
+        let mut visited = 0;
+
+        // Tarjan's algorithm following the links backwards, so a component is only completed
+        // after every component linking to it, without recursion.
+        for root in 0..size {
+            if order[root] != UNVISITED {
+                continue;
+            }
+
+            order[root] = visited;
+            low[root] = visited;
+            visited += 1;
+            stack.push(root);
+            on_stack[root] = true;
+            let mut frames = vec![(root, self.score_links(setup, root))];
+
+            while let Some((node, links)) = frames.last_mut() {
+                let node = *node;
+                if let Some((from, _)) = links.next() {
+                    if order[from] == UNVISITED {
+                        order[from] = visited;
+                        low[from] = visited;
+                        visited += 1;
+                        stack.push(from);
+                        on_stack[from] = true;
+                        frames.push((from, self.score_links(setup, from)));
+                    } else if on_stack[from] {
+                        low[node] = low[node].min(order[from]);
+                    }
+                    continue;
+                }
+
+                frames.pop();
+                if let Some((parent, _)) = frames.last() {
+                    low[*parent] = low[*parent].min(low[node]);
+                }
+                if low[node] == order[node] {
+                    let mut component = Vec::new();
+                    while let Some(member) = stack.pop() {
+                        on_stack[member] = false;
+                        component.push(member);
+                        if member == node {
+                            break;
+                        }
+                    }
+                    components.push(component);
+                }
+            }
+        }
+
+        components
+    }
+
+    /// Solves the linear system with Gaussian elimination with partial pivoting, returning None
+    /// if the graph has more than EXACT_MAX_NODES nodes or the matrix can't be allocated or is
+    /// singular.
+    fn exact(&self, following_prob: f64, setup: &RankSetup) -> Option<Vec<f64>> {
+        let size = self.size();
+        if size > EXACT_MAX_NODES {
+            return None;
+        }
+        let cells = size.checked_mul(size)?;
+        let mut a: Vec<f64> = Vec::new();
+        a.try_reserve_exact(cells).ok()?;
+        a.resize(cells, 0.0);
+
+        // Row i is the equation x_i - following_prob * sum(share * x_from) = b_i.
+        let mut b: Vec<f64> = setup
+            .teleport
+            .iter()
+            .map(|teleport| (1.0 - following_prob) * teleport)
+            .collect();
+        for i in 0..size {
+            let row = &mut a[i * size..(i + 1) * size];
+            row[i] += 1.0;
+            for (index, share) in self.score_links(setup, i) {
+                row[index] -= following_prob * share;
+            }
+        }
+
+        for column in 0..size {
+            let pivot = (column..size)
+                .max_by(|&r, &s| {
+                    a[r * size + column]
+                        .abs()
+                        .total_cmp(&a[s * size + column].abs())
+                })
+                .unwrap_or(column);
+            if a[pivot * size + column] == 0.0 {
+                return None;
+            }
+            if pivot != column {
+                let (upper, lower) = a.split_at_mut(pivot * size);
+                upper[column * size..(column + 1) * size].swap_with_slice(&mut lower[..size]);
+                b.swap(pivot, column);
+            }
+
+            let (upper, lower) = a.split_at_mut((column + 1) * size);
+            let pivot_row = &upper[column * size..];
+            let (b_upper, b_lower) = b.split_at_mut(column + 1);
+            let pivot_b = b_upper[column];
+            lower
+                .par_chunks_mut(size)
+                .zip(b_lower.par_iter_mut())
+                .for_each(|(row, b_row)| {
+                    let factor = row[column] / pivot_row[column];
+                    if factor != 0.0 {
+                        row[column..]
+                            .iter_mut()
+                            .zip(&pivot_row[column..])
+                            .for_each(|(value, pivot_value)| *value -= factor * pivot_value);
+                        *b_row -= factor * pivot_b;
+                    }
+                });
+        }
+
+        let mut x = vec![0.0; size];
+        for i in (0..size).rev() {
+            let row = &a[i * size..(i + 1) * size];
+            let known: f64 = (i + 1..size).map(|k| row[k] * x[k]).sum();
+            x[i] = (b[i] - known) / row[i];
+        }
+
+        Self::normalize(&mut x);
+        Some(x)
+    }
+}
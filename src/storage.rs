@@ -0,0 +1,292 @@
+//! This module provides the copy-on-write containers holding the graph data of Pagerank.
+//!
+//! The data is split into reference counted chunks, so cloning a graph only clones the chunk
+//! pointers and a mutation of a shared graph only copies the chunks it touches, instead of the
+//! whole graph.
+use crate::sharding::mix64;
+use std::collections::{HashMap, TryReserveError};
+use std::hash::{BuildHasherDefault, Hasher};
+use std::ops::{Index, IndexMut};
+use std::sync::Arc;
+
+/// The number of elements of a chunk, a power of two so locating an element is a shift and a
+/// mask.
+pub(crate) const CHUNK_SIZE: usize = 1024;
+
+/// A vector stored in chunks of CHUNK_SIZE elements that are shared between clones until they
+/// are mutated. Mutable indexing copies the chunk of the element if it is shared.
+///
+/// Fields:
+/// - chunks: The elements, every chunk but the last one holds exactly CHUNK_SIZE elements.
+/// - len: The number of elements.
+#[derive(Debug, Clone)]
+pub(crate) struct ChunkedVec<T> {
+    chunks: Vec<Arc<Vec<T>>>,
+    len: usize,
+}
+
+impl<T: Clone> ChunkedVec<T> {
+    pub(crate) fn new() -> Self {
+        ChunkedVec {
+            chunks: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Constructs a vector holding len clones of value.
+    pub(crate) fn from_elem(value: T, len: usize) -> Self {
+        let mut chunks = Vec::with_capacity(len.div_ceil(CHUNK_SIZE));
+        let mut remaining = len;
+        while remaining > 0 {
+            let chunk_len = remaining.min(CHUNK_SIZE);
+            chunks.push(Arc::new(vec![value.clone(); chunk_len]));
+            remaining -= chunk_len;
+        }
+        ChunkedVec { chunks, len }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(crate) fn push(&mut self, value: T) {
+        if self.len.is_multiple_of(CHUNK_SIZE) {
+            let mut chunk = Vec::with_capacity(CHUNK_SIZE);
+            chunk.push(value);
+            self.chunks.push(Arc::new(chunk));
+        } else if let Some(last) = self.chunks.last_mut() {
+            Arc::make_mut(last).push(value);
+        }
+        self.len += 1;
+    }
+
+    pub(crate) fn clear(&mut self) {
+        self.chunks.clear();
+        self.len = 0;
+    }
+
+    pub(crate) fn iter(&self) -> impl Iterator<Item = &T> + '_ {
+        self.chunks.iter().flat_map(|chunk| chunk.iter())
+    }
+
+    /// Returns the chunks, every chunk but the last one holds exactly CHUNK_SIZE elements.
+    pub(crate) fn chunks(&self) -> &[Arc<Vec<T>>] {
+        &self.chunks
+    }
+
+    /// Returns the chunks for bulk mutations, which should only call Arc::make_mut on the
+    /// chunks they actually change.
+    pub(crate) fn chunks_mut(&mut self) -> &mut [Arc<Vec<T>>] {
+        &mut self.chunks
+    }
+
+    /// Returns a copy of the vector extended to len elements with clones of value, sharing the
+    /// full chunks with self. Fails without side effects if the memory can't be reserved.
+    pub(crate) fn try_resized(&self, len: usize, value: T) -> Result<Self, TryReserveError> {
+        let mut resized = self.clone();
+        if len <= self.len {
+            return Ok(resized);
+        }
+
+        resized
+            .chunks
+            .try_reserve(len.div_ceil(CHUNK_SIZE) - self.chunks.len())?;
+        if let Some(last) = resized.chunks.last_mut() {
+            let missing = (CHUNK_SIZE - last.len()).min(len - self.len);
+            if missing > 0 {
+                let mut chunk = Vec::new();
+                chunk.try_reserve_exact(last.len() + missing)?;
+                chunk.extend_from_slice(last);
+                chunk.resize(chunk.len() + missing, value.clone());
+                *last = Arc::new(chunk);
+                resized.len += missing;
+            }
+        }
+        while resized.len < len {
+            let chunk_len = (len - resized.len).min(CHUNK_SIZE);
+            let mut chunk = Vec::new();
+            chunk.try_reserve_exact(chunk_len)?;
+            chunk.resize(chunk_len, value.clone());
+            resized.chunks.push(Arc::new(chunk));
+            resized.len += chunk_len;
+        }
+        Ok(resized)
+    }
+
+    /// Applies reset to every element. Shared chunks are replaced by chunks of fresh clones
+    /// instead of being copied first.
+    pub(crate) fn reset<F>(&mut self, fresh: T, reset: F)
+    where
+        F: Fn(&mut T),
+    {
+        for chunk in &mut self.chunks {
+            match Arc::get_mut(chunk) {
+                Some(elements) => elements.iter_mut().for_each(&reset),
+                None => *chunk = Arc::new(vec![fresh.clone(); chunk.len()]),
+            }
+        }
+    }
+
+    /// Returns the heap memory used by the chunks, not counting memory owned by the elements.
+    #[cfg(feature = "metrics")]
+    pub(crate) fn heap_bytes(&self) -> usize {
+        let elements: usize = self.chunks.iter().map(|chunk| chunk.capacity()).sum();
+        self.chunks.capacity() * size_of::<Arc<Vec<T>>>() + elements * size_of::<T>()
+    }
+}
+
+impl<T> Index<usize> for ChunkedVec<T> {
+    type Output = T;
+
+    #[inline]
+    fn index(&self, index: usize) -> &T {
+        &self.chunks[index / CHUNK_SIZE][index % CHUNK_SIZE]
+    }
+}
+
+impl<T: Clone> IndexMut<usize> for ChunkedVec<T> {
+    #[inline]
+    fn index_mut(&mut self, index: usize) -> &mut T {
+        &mut Arc::make_mut(&mut self.chunks[index / CHUNK_SIZE])[index % CHUNK_SIZE]
+    }
+}
+
+impl<T: Clone> From<Vec<T>> for ChunkedVec<T> {
+    fn from(elements: Vec<T>) -> Self {
+        let len = elements.len();
+        let mut elements = elements.into_iter();
+        let chunks = (0..len.div_ceil(CHUNK_SIZE))
+            .map(|_| Arc::new(elements.by_ref().take(CHUNK_SIZE).collect()))
+            .collect();
+        ChunkedVec { chunks, len }
+    }
+}
+
+/// Hashes the keys of ShardedMap, which are already mixed, with a single multiplication so the
+/// bits selecting the shard don't also select the slot within the shard.
+#[derive(Default)]
+struct MixedKeyHasher(u64);
+
+impl Hasher for MixedKeyHasher {
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.0.wrapping_mul(0x9e3779b97f4a7c15)
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 = self.0.rotate_left(8) ^ byte as u64;
+        }
+    }
+
+    #[inline]
+    fn write_u64(&mut self, mixed_key: u64) {
+        self.0 = mixed_key;
+    }
+}
+
+type Shard = HashMap<u64, usize, BuildHasherDefault<MixedKeyHasher>>;
+
+/// A map from node keys to node indices split into a power of two number of shards of about
+/// CHUNK_SIZE entries that are shared between clones until they are mutated.
+///
+/// Keys are stored mixed by mix64, a bijection, so a key is hashed once both to select its shard
+/// and to find its slot within the shard.
+///
+/// Fields:
+/// - shards: The entries, a key belongs to the shard selected by the high bits of its mix.
+/// - len: The number of entries.
+#[derive(Debug, Clone)]
+pub(crate) struct ShardedMap {
+    shards: Vec<Arc<Shard>>,
+    len: usize,
+}
+
+impl ShardedMap {
+    /// Constructs a map with room for capacity entries.
+    pub(crate) fn with_capacity(capacity: usize) -> Self {
+        let shards = Self::shard_count(capacity);
+        let shard_capacity = capacity.div_ceil(shards);
+        ShardedMap {
+            shards: (0..shards)
+                .map(|_| {
+                    Arc::new(Shard::with_capacity_and_hasher(
+                        shard_capacity,
+                        Default::default(),
+                    ))
+                })
+                .collect(),
+            len: 0,
+        }
+    }
+
+    /// Returns the number of shards for capacity entries, a power of two.
+    fn shard_count(capacity: usize) -> usize {
+        capacity.div_ceil(CHUNK_SIZE).max(1).next_power_of_two()
+    }
+
+    #[inline]
+    fn shard_of(mixed_key: u64, shards: usize) -> usize {
+        (mixed_key >> 32) as usize & (shards - 1)
+    }
+
+    #[inline]
+    pub(crate) fn get(&self, key: &usize) -> Option<&usize> {
+        let mixed_key = mix64(*key as u64);
+        self.shards[Self::shard_of(mixed_key, self.shards.len())].get(&mixed_key)
+    }
+
+    pub(crate) fn insert(&mut self, key: usize, index: usize) {
+        let mixed_key = mix64(key as u64);
+        let shard = Self::shard_of(mixed_key, self.shards.len());
+        if Arc::make_mut(&mut self.shards[shard])
+            .insert(mixed_key, index)
+            .is_none()
+        {
+            self.len += 1;
+        }
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.len
+    }
+
+    pub(crate) fn clear(&mut self) {
+        for shard in &mut self.shards {
+            match Arc::get_mut(shard) {
+                Some(entries) => entries.clear(),
+                None => *shard = Arc::new(Shard::default()),
+            }
+        }
+        self.len = 0;
+    }
+
+    /// Returns a copy of the map with room for capacity entries, re-sharded for that capacity.
+    /// Fails without side effects if the memory can't be reserved.
+    pub(crate) fn try_resharded(&self, capacity: usize) -> Result<Self, TryReserveError> {
+        let count = Self::shard_count(capacity.max(self.len));
+        let mut shards: Vec<Shard> = Vec::new();
+        shards.try_reserve_exact(count)?;
+        for _ in 0..count {
+            let mut shard = Shard::default();
+            shard.try_reserve(capacity.max(self.len).div_ceil(count))?;
+            shards.push(shard);
+        }
+
+        for (&mixed_key, &index) in self.shards.iter().flat_map(|shard| shard.iter()) {
+            shards[Self::shard_of(mixed_key, count)].insert(mixed_key, index);
+        }
+        Ok(ShardedMap {
+            shards: shards.into_iter().map(Arc::new).collect(),
+            len: self.len,
+        })
+    }
+
+    /// Returns an estimate of the heap memory used by the map.
+    #[cfg(feature = "metrics")]
+    pub(crate) fn heap_bytes(&self) -> usize {
+        let entry = size_of::<u64>() + size_of::<usize>() + 1;
+        let entries: usize = self.shards.iter().map(|shard| shard.capacity()).sum();
+        self.shards.capacity() * size_of::<Arc<Shard>>() + entries * entry
+    }
+}
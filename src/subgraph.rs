@@ -0,0 +1,57 @@
+//! This module provides the extraction of the subgraph induced by the highest ranked nodes, to
+//! produce small summaries of large graphs that can be visualized or inspected by hand.
+use crate::config::RankConfig;
+use crate::Pagerank;
+use std::collections::HashMap;
+
+/// The subgraph induced by the top-k ranked nodes of a graph.
+///
+/// Fields:
+/// - nodes: The keys and scores of the top-k nodes, sorted by descending score. The scores are
+///   the ones of the full graph.
+/// - links: The links between the top-k nodes as (from, to) keys, sorted by source and then
+///   target. Repeated links appear once per link call.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RankedSubgraph {
+    pub nodes: Vec<(usize, f64)>,
+    pub links: Vec<(usize, usize)>,
+}
+
+impl Pagerank {
+    /// Ranks the graph and returns the subgraph induced by its k highest ranked nodes.
+    ///
+    /// # Arguments
+    ///
+    /// * config - The parameters of the computation.
+    /// * k - The maximum number of nodes of the subgraph.
+    ///
+    /// # Examples
+    ///
+    /// let summary = pagerank.top_k_subgraph(&RankConfig::default(), 50);
+    /// for (from, to) in summary.links {
+    ///     println!("{} -> {}", from, to);
+    /// }
+    ///
+    pub fn top_k_subgraph(&self, config: &RankConfig, k: usize) -> RankedSubgraph {
+        let mut nodes = self.rank_with_config(config);
+        nodes.truncate(k);
+
+        let selected: HashMap<usize, usize> = nodes
+            .iter()
+            .filter_map(|&(key, _)| Some((self.index_of(key)?, key)))
+            .collect();
+
+        let mut links: Vec<(usize, usize)> = selected
+            .iter()
+            .flat_map(|(&to_index, &to)| {
+                self.in_links_of(to_index)
+                    .iter()
+                    .filter_map(|from_index| selected.get(from_index))
+                    .map(move |&from| (from, to))
+            })
+            .collect();
+        links.sort_unstable();
+
+        RankedSubgraph { nodes, links }
+    }
+}
@@ -0,0 +1,258 @@
+//! This module provides rank computations that only keep the k best nodes and summary statistics
+//! of the scores instead of the full result, so batch jobs ranking huge edge lists under a memory
+//! limit only need memory for the graph and the score vectors of the computation.
+use crate::config::{RankConfig, TieBreak};
+use crate::errors::PagerankError;
+use crate::loader::open_edge_list;
+use crate::Pagerank;
+use std::cmp::Ordering;
+use std::collections::BinaryHeap;
+use std::io::BufRead;
+use std::path::Path;
+
+/// The outcome of a rank computation keeping only the best nodes.
+///
+/// The statistics cover every node of the result as rank_with_config would return it, i.e.
+/// after applying drop_below, score_floor and score_ceiling.
+///
+/// Fields:
+/// - top: Up to k (key, score) pairs with the highest scores, sorted by descending score with
+///   ties ordered by config.tie_break, so with Key or SeededHash they are the first k pairs of
+///   the result of rank_with_config. Unordered ties are ordered by key.
+/// - nodes: The number of nodes of the result.
+/// - min: The lowest score, 0 if there are no nodes.
+/// - max: The highest score, 0 if there are no nodes.
+/// - mean: The mean score, 0 if there are no nodes.
+/// - std_dev: The population standard deviation of the scores, 0 if there are no nodes.
+/// - top_mass: The sum of the scores in top, which measures how concentrated the scores are.
+/// - iterations: The number of iterations of the computation.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TopKRank {
+    pub top: Vec<(usize, f64)>,
+    pub nodes: usize,
+    pub min: f64,
+    pub max: f64,
+    pub mean: f64,
+    pub std_dev: f64,
+    pub top_mass: f64,
+    pub iterations: usize,
+}
+
+/// A heap entry, the greatest entry is the one ranked last. Entries of a run of ties are only
+/// compared with the tie-break.
+struct Candidate {
+    key: usize,
+    score: f64,
+    tie_break: TieBreak,
+    tied: bool,
+}
+
+impl Ord for Candidate {
+    fn cmp(&self, other: &Self) -> Ordering {
+        let (a, b) = (&(self.key, self.score), &(other.key, other.score));
+        if self.tied {
+            self.tie_break.tie_order(a, b)
+        } else {
+            self.tie_break.compare(a, b)
+        }
+    }
+}
+
+impl PartialOrd for Candidate {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl PartialEq for Candidate {
+    fn eq(&self, other: &Self) -> bool {
+        self.cmp(other) == Ordering::Equal
+    }
+}
+
+impl Eq for Candidate {}
+
+impl Pagerank {
+    /// Computes the PageRank scores like rank_with_config, keeping only the k best nodes and
+    /// summary statistics of the scores.
+    ///
+    /// The scores are streamed through a heap of k entries, so no result of the size of the
+    /// graph is built.
+    ///
+    /// # Arguments
+    ///
+    /// * config - The parameters of the computation.
+    /// * k - The number of best nodes kept.
+    ///
+    /// # Examples
+    ///
+    /// let result = pagerank.rank_top_k(&RankConfig::default(), 100);
+    /// println!("The top 100 of {} nodes hold {} of the score", result.nodes, result.top_mass);
+    ///
+    pub fn rank_top_k(&self, config: &RankConfig, k: usize) -> TopKRank {
+        Self::install(config, || {
+            let (p, iterations, _, _) = self.iterate(config, &self.setup(config, None), |_| true);
+
+            let mut heap = BinaryHeap::with_capacity(k.min(p.len()) + 1);
+            let mut nodes = 0;
+            let mut min = f64::INFINITY;
+            let mut max = f64::NEG_INFINITY;
+            let mut mean = 0.0;
+            let mut squares = 0.0;
+
+            for (index, &p_i) in p.iter().enumerate() {
+                let Some(score) = config.emitted_score(p_i) else {
+                    continue;
+                };
+
+                // Welford's online algorithm, numerically stable for millions of tiny scores.
+                nodes += 1;
+                min = min.min(score);
+                max = max.max(score);
+                let delta = score - mean;
+                mean += delta / nodes as f64;
+                squares += delta * (score - mean);
+
+                let candidate = Candidate {
+                    key: self.key_of(index),
+                    score,
+                    tie_break: config.tie_break,
+                    tied: false,
+                };
+                Self::offer(&mut heap, k, candidate);
+            }
+
+            let mut top = Self::into_ranked(heap);
+            if nodes > k {
+                self.order_last_ties(config, &p, &mut top);
+            } else {
+                config.tie_break.order_ties(&mut top);
+            }
+            if nodes == 0 {
+                (min, max) = (0.0, 0.0);
+            }
+
+            TopKRank {
+                top_mass: top.iter().map(|&(_, score)| score).sum(),
+                top,
+                nodes,
+                min,
+                max,
+                mean,
+                std_dev: if nodes == 0 {
+                    0.0
+                } else {
+                    (squares / nodes as f64).sqrt()
+                },
+                iterations,
+            }
+        })
+    }
+
+    /// Keeps candidate if it is ranked among the k first entries of heap.
+    fn offer(heap: &mut BinaryHeap<Candidate>, k: usize, candidate: Candidate) {
+        if heap.len() < k {
+            heap.push(candidate);
+        } else if heap.peek().is_some_and(|last| candidate < *last) {
+            heap.pop();
+            heap.push(candidate);
+        }
+    }
+
+    fn into_ranked(heap: BinaryHeap<Candidate>) -> Vec<(usize, f64)> {
+        heap.into_sorted_vec()
+            .into_iter()
+            .map(|candidate| (candidate.key, candidate.score))
+            .collect()
+    }
+
+    /// Orders the ties of top, the best nodes by exact score, like the full result.
+    ///
+    /// The last run of ties of top may continue past it, and the full result orders the whole
+    /// run by the tie-break, so its part in top is replaced by the best tied nodes according to
+    /// the tie-break, found with a second pass over the scores.
+    fn order_last_ties(&self, config: &RankConfig, p: &[f64], top: &mut Vec<(usize, f64)>) {
+        if config.tie_break == TieBreak::Unordered || top.is_empty() {
+            return;
+        }
+
+        let mut start = 0;
+        let mut end = TieBreak::tie_run_end(top, start);
+        while end < top.len() {
+            start = end;
+            end = TieBreak::tie_run_end(top, start);
+        }
+        let first = top[start].1;
+        let mut heap = BinaryHeap::with_capacity(top.len() - start + 1);
+        for (index, &p_i) in p.iter().enumerate() {
+            match config.emitted_score(p_i) {
+                Some(score) if score <= first && TieBreak::is_tie(first, score) => {
+                    let candidate = Candidate {
+                        key: self.key_of(index),
+                        score,
+                        tie_break: config.tie_break,
+                        tied: true,
+                    };
+                    Self::offer(&mut heap, top.len() - start, candidate);
+                }
+                _ => {}
+            }
+        }
+
+        top.truncate(start);
+        config.tie_break.order_ties(top);
+        top.extend(Self::into_ranked(heap));
+    }
+
+    /// Loads an edge list into a new graph and ranks it with rank_top_k, so only the graph, the
+    /// k best nodes and the summary statistics are ever held in memory.
+    ///
+    /// # Arguments
+    ///
+    /// * reader - The edge list, in the format of load_edge_list.
+    /// * capacity - The maximum number of nodes of the graph.
+    /// * config - The parameters of the computation.
+    /// * k - The number of best nodes kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns a PagerankError if load_edge_list fails.
+    ///
+    /// # Examples
+    ///
+    /// let stdin = std::io::stdin().lock();
+    /// let result = Pagerank::rank_top_k_edge_list(stdin, 50_000_000, &RankConfig::default(), 1000)?;
+    ///
+    pub fn rank_top_k_edge_list<R: BufRead>(
+        reader: R,
+        capacity: usize,
+        config: &RankConfig,
+        k: usize,
+    ) -> Result<TopKRank, PagerankError> {
+        let mut graph = Pagerank::new(capacity);
+        graph.load_edge_list(reader)?;
+        Ok(graph.rank_top_k(config, k))
+    }
+
+    /// Loads an edge list file into a new graph and ranks it with rank_top_k, like
+    /// rank_top_k_edge_list.
+    ///
+    /// # Arguments
+    ///
+    /// * path - The path of the edge list, plain or compressed with gzip or zstd.
+    /// * capacity - The maximum number of nodes of the graph.
+    /// * config - The parameters of the computation.
+    /// * k - The number of best nodes kept.
+    ///
+    /// # Errors
+    ///
+    /// Returns a PagerankError if load_edge_list_file fails.
+    pub fn rank_top_k_edge_list_file<P: AsRef<Path>>(
+        path: P,
+        capacity: usize,
+        config: &RankConfig,
+        k: usize,
+    ) -> Result<TopKRank, PagerankError> {
+        Self::rank_top_k_edge_list(open_edge_list(path.as_ref())?, capacity, config, k)
+    }
+}
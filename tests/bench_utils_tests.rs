@@ -0,0 +1,57 @@
+#![cfg(feature = "bench_utils")]
+
+use float_cmp::approx_eq;
+use pagerank_rs::bench_utils::{
+    max_score_difference, measure, preferential_attachment_graph, ring_graph, star_graph,
+    top_k_overlap, uniform_random_edges, uniform_random_graph,
+};
+use pagerank_rs::RankConfig;
+
+#[test]
+fn test_generators_are_deterministic_per_seed() {
+    let a = uniform_random_graph(200, 10, 42);
+    let b = uniform_random_graph(200, 10, 42);
+    let config = RankConfig::new(0.85, 0.0001);
+
+    assert_eq!(a.rank_with_config(&config), b.rank_with_config(&config));
+    assert_eq!(
+        uniform_random_edges(200, 10, 42),
+        uniform_random_edges(200, 10, 42)
+    );
+
+    let a = preferential_attachment_graph(200, 3, 7);
+    let b = preferential_attachment_graph(200, 3, 7);
+    assert_eq!(a.rank_with_config(&config), b.rank_with_config(&config));
+}
+
+#[test]
+fn test_standard_shapes() {
+    let config = RankConfig::new(0.85, 0.0001);
+
+    let ring = ring_graph(4).rank_with_config(&config);
+    assert_eq!(ring.len(), 4);
+    assert!(ring
+        .iter()
+        .all(|&(_, score)| approx_eq!(f64, score, 0.25, epsilon = 0.0001)));
+
+    let star = star_graph(5).rank_with_config(&config);
+    assert_eq!(star[0].0, 0);
+    assert!(star[0].1 > star[1].1);
+}
+
+#[test]
+fn test_measure_and_compare() {
+    let graph = preferential_attachment_graph(500, 4, 42);
+    let loose = measure(&graph, &RankConfig::new(0.85, 0.01));
+    let strict = measure(&graph, &RankConfig::new(0.85, 0.000001));
+
+    assert!(loose.iterations > 0);
+    assert!(strict.iterations >= loose.iterations);
+    assert!(max_score_difference(&loose.ranked, &strict.ranked) < 0.01);
+    assert_eq!(max_score_difference(&strict.ranked, &strict.ranked), 0.0);
+    assert_eq!(top_k_overlap(&strict.ranked, &strict.ranked, 10), 1.0);
+    assert_eq!(
+        top_k_overlap(&[(1, 0.6), (2, 0.4)], &[(1, 0.5), (3, 0.5)], 2),
+        0.5
+    );
+}
@@ -0,0 +1,54 @@
+#[cfg(test)]
+mod tests {
+    use pagerank_rs::Pagerank;
+
+    const EDGE_LIST: &str = "# A comment\n\
+                             0 1\n\
+                             \n\
+                             1,2\n\
+                             2\t0\t3\n";
+
+    #[test]
+    fn test_should_write_an_edge_list_that_loads_back() -> Result<(), Box<dyn std::error::Error>> {
+        let mut page_rank = Pagerank::new(3);
+        page_rank.load_edge_list(EDGE_LIST.as_bytes())?;
+
+        let mut edge_list = Vec::new();
+        assert_eq!(3, page_rank.write_edge_list(&mut edge_list)?);
+        assert_eq!("2 0 3\n0 1\n1 2\n", String::from_utf8(edge_list.clone())?);
+
+        let mut remapped = Vec::new();
+        page_rank.write_edge_list_remapped(&mut remapped, |key| key + 10)?;
+        assert_eq!("12 10 3\n10 11\n11 12\n", String::from_utf8(remapped)?);
+
+        let mut reloaded = Pagerank::new(3);
+        assert_eq!(3, reloaded.load_edge_list(edge_list.as_slice())?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_write_an_anonymized_edge_list() -> Result<(), Box<dyn std::error::Error>> {
+        let mut page_rank = Pagerank::new(3);
+        page_rank.link(100, 200)?;
+        page_rank.link(200, 300)?;
+        page_rank.link(300, 100)?;
+
+        let mut anonymized = Vec::new();
+        let mapping = page_rank.write_edge_list_anonymized(&mut anonymized, 42)?;
+
+        let mut anonymized_keys: Vec<usize> = mapping.values().copied().collect();
+        anonymized_keys.sort_unstable();
+        assert_eq!(vec![0, 1, 2], anonymized_keys);
+
+        let mut expected = Vec::new();
+        page_rank.write_edge_list_remapped(&mut expected, |key| mapping[&key])?;
+        assert_eq!(expected, anonymized);
+
+        let mut again = Vec::new();
+        assert_eq!(
+            mapping,
+            page_rank.write_edge_list_anonymized(&mut again, 42)?
+        );
+        Ok(())
+    }
+}
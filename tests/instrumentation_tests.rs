@@ -0,0 +1,44 @@
+#![cfg(feature = "metrics")]
+
+use metrics_util::debugging::{DebugValue, DebuggingRecorder};
+use pagerank_rs::instrumentation::{EDGE_VISITS, ITERATIONS, MEMORY_BYTES, RESIDUAL};
+use pagerank_rs::{Pagerank, RankConfig};
+use std::collections::HashMap;
+
+#[test]
+fn test_records_the_metrics_of_a_computation() -> Result<(), Box<dyn std::error::Error>> {
+    let mut page_rank = Pagerank::new(3);
+    page_rank.link(0, 1)?;
+    page_rank.link(1, 2)?;
+
+    let recorder = DebuggingRecorder::new();
+    let snapshotter = recorder.snapshotter();
+    let config = RankConfig::new(0.85, 0.0001);
+    let result =
+        metrics::with_local_recorder(&recorder, || page_rank.rank_budgeted(&config, usize::MAX));
+
+    let values: HashMap<String, DebugValue> = snapshotter
+        .snapshot()
+        .into_vec()
+        .into_iter()
+        .map(|(key, _, _, value)| (key.key().name().to_string(), value))
+        .collect();
+    // Every iteration visits the 3 nodes and the 2 links.
+    assert_eq!(
+        Some(&DebugValue::Counter(result.iterations as u64)),
+        values.get(ITERATIONS)
+    );
+    assert_eq!(
+        Some(&DebugValue::Counter(5 * result.iterations as u64)),
+        values.get(EDGE_VISITS)
+    );
+    match values.get(RESIDUAL) {
+        Some(DebugValue::Gauge(residual)) => assert_eq!(result.change, residual.into_inner()),
+        other => panic!("Expected a residual gauge but got {:?}", other),
+    }
+    match values.get(MEMORY_BYTES) {
+        Some(DebugValue::Gauge(bytes)) => assert!(bytes.into_inner() > 0.0),
+        other => panic!("Expected a memory gauge but got {:?}", other),
+    }
+    Ok(())
+}
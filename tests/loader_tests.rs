@@ -0,0 +1,129 @@
+#[cfg(test)]
+mod tests {
+    use pagerank_rs::errors::PagerankError;
+    use pagerank_rs::{Pagerank, RankConfig};
+
+    const EDGE_LIST: &str = "# A comment\n\
+                             0 1\n\
+                             \n\
+                             1,2\n\
+                             2\t0\t3\n";
+
+    #[test]
+    fn test_should_load_an_edge_list() -> Result<(), Box<dyn std::error::Error>> {
+        let mut page_rank = Pagerank::new(3);
+
+        let loaded = page_rank.load_edge_list(EDGE_LIST.as_bytes())?;
+
+        assert_eq!(3, loaded);
+        let result = page_rank.rank(0.85, 0.0001);
+        assert!(result
+            .iter()
+            .all(|&(_, rank)| (rank - 1.0 / 3.0).abs() < 0.001));
+        let result = page_rank.rank_filtered(&RankConfig::default(), |edge_type| edge_type == 3);
+        assert_eq!(0, result[0].0, "Only the link from 2 to 0 has type 3");
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_fail_on_the_first_malformed_line() {
+        let mut page_rank = Pagerank::new(3);
+
+        let result = page_rank.load_edge_list("0 1\n1 two\n1 2\n".as_bytes());
+
+        match result {
+            Err(PagerankError::ParseError(message)) => assert!(message.starts_with("Line 2")),
+            other => panic!("Expected a parse error but got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn test_should_report_skipped_records_when_loading_leniently() {
+        let mut page_rank = Pagerank::new(3);
+
+        let report =
+            page_rank.load_edge_list_lenient("0 1\n1 two\n1 2\n2 3\n0 1 2 3 4\n".as_bytes());
+
+        assert_eq!(2, report.loaded);
+        assert_eq!(3, report.skipped);
+        let lines: Vec<_> = report.errors.iter().map(|error| error.line).collect();
+        assert_eq!(vec![2, 4, 5], lines);
+        assert!(matches!(
+            report.errors[1].error,
+            PagerankError::CapacityError(_)
+        ));
+    }
+
+    #[test]
+    fn test_should_not_add_the_nodes_of_skipped_records_when_loading_leniently() {
+        let mut page_rank = Pagerank::new(3);
+
+        // Only one of the nodes of "5 6" fits, and only the source of "9 1" is new.
+        let report = page_rank.load_edge_list_lenient("0 1\n5 6\n1 0\n2 0\n9 1\n".as_bytes());
+
+        assert_eq!(3, report.loaded);
+        let lines: Vec<_> = report.errors.iter().map(|error| error.line).collect();
+        assert_eq!(vec![2, 5], lines);
+        let mut keys: Vec<_> = page_rank
+            .rank(0.85, 0.0001)
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect();
+        keys.sort_unstable();
+        assert_eq!(vec![0, 1, 2], keys);
+    }
+
+    fn temp_path(name: &str) -> std::path::PathBuf {
+        std::env::temp_dir().join(format!("pagerank_rs_{}_{}", std::process::id(), name))
+    }
+
+    #[test]
+    fn test_should_load_an_edge_list_file() -> Result<(), Box<dyn std::error::Error>> {
+        let path = temp_path("edges.txt");
+        std::fs::write(&path, EDGE_LIST)?;
+        let mut page_rank = Pagerank::new(3);
+
+        let loaded = page_rank.load_edge_list_file(&path);
+        std::fs::remove_file(&path)?;
+
+        assert_eq!(3, loaded?);
+        Ok(())
+    }
+
+    #[cfg(feature = "gzip")]
+    #[test]
+    fn test_should_load_a_gzip_compressed_edge_list_file() -> Result<(), Box<dyn std::error::Error>>
+    {
+        use std::io::Write;
+
+        let path = temp_path("edges.txt.gz");
+        let mut encoder = flate2::write::GzEncoder::new(
+            std::fs::File::create(&path)?,
+            flate2::Compression::default(),
+        );
+        encoder.write_all(EDGE_LIST.as_bytes())?;
+        encoder.finish()?;
+        let mut page_rank = Pagerank::new(3);
+
+        let loaded = page_rank.load_edge_list_file(&path);
+        std::fs::remove_file(&path)?;
+
+        assert_eq!(3, loaded?);
+        Ok(())
+    }
+
+    #[cfg(feature = "zstd")]
+    #[test]
+    fn test_should_load_a_zstd_compressed_edge_list_file() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let path = temp_path("edges.txt.zst");
+        std::fs::write(&path, zstd::stream::encode_all(EDGE_LIST.as_bytes(), 0)?)?;
+        let mut page_rank = Pagerank::new(3);
+
+        let report = page_rank.load_edge_list_file_lenient(&path);
+        std::fs::remove_file(&path)?;
+
+        assert_eq!(3, report?.loaded);
+        Ok(())
+    }
+}
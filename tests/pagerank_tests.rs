@@ -1,7 +1,12 @@
 #[cfg(test)]
 mod tests {
     use float_cmp::approx_eq;
-    use pagerank_rs::Pagerank; // You might need the 'float-cmp' crate for floating-point comparisons
+    use pagerank_rs::errors::PagerankError;
+    use pagerank_rs::{
+        EdgeType, InitialDistribution, NodeOrder, Pagerank, PagerankNamespaces, PagerankPipeline,
+        RankConfig, ScoreHistory, ScorePublisher, Solver, TieBreak, Trend, DEFAULT_EDGE_TYPE,
+        EXACT_MAX_NODES, NO_PROVENANCE, TIE_EPSILON,
+    }; // You might need the 'float-cmp' crate for floating-point comparisons
 
     fn round_to_places(num: f64, places: u32) -> f64 {
         let multiplier = 10f64.powi(places as i32);
@@ -12,8 +17,18 @@ mod tests {
         round_to_places(100.000 * f, 1)
     }
 
-    fn assert_rank(page_rank: &mut Pagerank, expected: &[(usize, f64)], tolerance: f64) {
-        let mut expected_entries = &expected[..];
+    fn percentages(result: Vec<(usize, f64)>) -> Vec<(usize, f64)> {
+        let mut result: Vec<_> = result
+            .into_iter()
+            .map(|(node_id, node_rank)| (node_id, to_percentage(node_rank)))
+            .collect();
+        // Ties are returned in an unspecified order.
+        result.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap().then(a.0.cmp(&b.0)));
+        result
+    }
+
+    fn assert_rank(page_rank: &Pagerank, expected: &[(usize, f64)], tolerance: f64) {
+        let mut expected_entries = expected;
         let result = page_rank.rank(0.85, tolerance);
 
         for (node_id, node_rank) in result {
@@ -78,11 +93,11 @@ mod tests {
         let mut page_rank = Pagerank::new(3);
         page_rank.link(0, 1)?;
         let expected = vec![(1, 64.9), (0, 35.1)];
-        assert_rank(&mut page_rank, &expected, 0.0001);
+        assert_rank(&page_rank, &expected, 0.0001);
 
         page_rank.link(1, 2)?;
         let expected = vec![(2, 47.4), (1, 34.1), (0, 18.4)];
-        assert_rank(&mut page_rank, &expected, 0.0001);
+        assert_rank(&page_rank, &expected, 0.0001);
         Ok(())
     }
 
@@ -95,14 +110,14 @@ mod tests {
         page_rank.link(0, 1)?;
 
         let expected = vec![(1, 64.9), (0, 35.1)];
-        assert_rank(&mut page_rank, &expected, 0.0001);
+        assert_rank(&page_rank, &expected, 0.0001);
         Ok(())
     }
 
     #[test]
     fn test_should_not_fail_when_calculating_the_rank_of_an_empty_graph(
     ) -> Result<(), Box<dyn std::error::Error>> {
-        let mut page_rank = Pagerank::new(1);
+        let page_rank = Pagerank::new(1);
 
         let result = page_rank.rank(0.85, 0.0001);
 
@@ -123,7 +138,7 @@ mod tests {
         page_rank.link(1, 2)?;
 
         let expected = vec![(2, 57.4), (0, 21.3), (1, 21.3)];
-        assert_rank(&mut page_rank, &expected, 0.0001);
+        assert_rank(&page_rank, &expected, 0.0001);
         Ok(())
     }
 
@@ -138,7 +153,7 @@ mod tests {
         page_rank.link(1, 2)?; // Duplicate link
 
         let expected = vec![(2, 57.4), (0, 21.3), (1, 21.3)];
-        assert_rank(&mut page_rank, &expected, 0.0001);
+        assert_rank(&page_rank, &expected, 0.0001);
         Ok(())
     }
 
@@ -151,7 +166,7 @@ mod tests {
         page_rank.link(2, 2)?; // Node 2 links to itself, forming a star graph
 
         let expected = vec![(2, 90.0), (0, 5.0), (1, 5.0)];
-        assert_rank(&mut page_rank, &expected, 0.0001);
+        assert_rank(&page_rank, &expected, 0.0001);
         Ok(())
     }
 
@@ -165,7 +180,7 @@ mod tests {
         page_rank.link(4, 0)?; // Creates a circular graph
 
         let expected = vec![(0, 20.0), (1, 20.0), (2, 20.0), (3, 20.0), (4, 20.0)];
-        assert_rank(&mut page_rank, &expected, 0.0001);
+        assert_rank(&page_rank, &expected, 0.0001);
         Ok(())
     }
 
@@ -179,7 +194,7 @@ mod tests {
         page_rank.link(2, 2)?; // Node 2 links to itself, forming a converging graph
 
         let expected = vec![(2, 87.9), (1, 7.1), (0, 5.0)];
-        assert_rank(&mut page_rank, &expected, 0.0001);
+        assert_rank(&page_rank, &expected, 0.0001);
         Ok(())
     }
 
@@ -219,7 +234,1013 @@ mod tests {
             (9, 1.6),  // Node 'j'
             (10, 1.6), // Node 'k'
         ];
-        assert_rank(&mut page_rank, &expected, 0.0001);
+        assert_rank(&page_rank, &expected, 0.0001);
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_keep_ingesting_while_a_pipeline_snapshot_is_ranked(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let pipeline = std::sync::Arc::new(PagerankPipeline::new(3));
+        pipeline.link(0, 1)?;
+
+        let snapshot = pipeline.swap();
+        let ingester = {
+            let pipeline = pipeline.clone();
+            std::thread::spawn(move || pipeline.link(1, 2))
+        };
+        let expected = vec![(1, 64.9), (0, 35.1)];
+        assert_rank(&snapshot, &expected, 0.0001);
+        ingester.join().unwrap()?;
+
+        let snapshot = pipeline.swap();
+        let expected = vec![(2, 47.4), (1, 34.1), (0, 18.4)];
+        assert_rank(&snapshot, &expected, 0.0001);
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_not_see_links_added_after_a_snapshot_was_taken(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut page_rank = Pagerank::new(3);
+        page_rank.link(0, 1)?;
+
+        let snapshot = page_rank.snapshot();
+        page_rank.link(1, 2)?;
+
+        let expected = vec![(1, 64.9), (0, 35.1)];
+        assert_rank(&snapshot, &expected, 0.0001);
+        let expected = vec![(2, 47.4), (1, 34.1), (0, 18.4)];
+        assert_rank(&page_rank, &expected, 0.0001);
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_not_see_mutations_of_a_large_graph_after_a_snapshot_was_taken(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        // Spans several storage chunks, so mutations copy some chunks and share the others.
+        let mut page_rank = Pagerank::new(5000);
+        for node in 0..4999 {
+            page_rank.link(node, node + 1)?;
+        }
+        let expected = page_rank.rank(0.85, 0.0001);
+
+        let snapshot = page_rank.snapshot();
+        page_rank.link(4999, 0)?;
+        page_rank.unlink_all_from(10);
+        page_rank.grow_capacity(5000)?;
+        page_rank.link(9000, 20)?;
+        page_rank.clear();
+
+        assert_eq!(expected, snapshot.rank(0.85, 0.0001));
+        assert_eq!(Vec::<(usize, f64)>::new(), page_rank.rank(0.85, 0.0001));
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_not_teleport_to_excluded_nodes() -> Result<(), Box<dyn std::error::Error>> {
+        let mut page_rank = Pagerank::new(3);
+        page_rank.link(0, 2)?;
+        page_rank.link(1, 2)?;
+        page_rank.link(2, 2)?;
+
+        let mut config = RankConfig::new(0.85, 0.0001);
+        config.no_teleport.insert(0);
+        config.no_teleport.insert(1);
+        let result = page_rank.rank_with_config(&config);
+
+        assert_eq!(vec![(2, 100.0), (0, 0.0), (1, 0.0)], percentages(result));
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_not_propagate_the_score_of_non_emitting_nodes(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut page_rank = Pagerank::new(3);
+        page_rank.link(0, 1)?;
+        page_rank.link(1, 2)?;
+
+        let mut config = RankConfig::new(0.85, 0.0001);
+        config.no_emit.insert(1);
+        let result = percentages(page_rank.rank_with_config(&config));
+
+        assert_eq!(1, result[0].0);
+        assert_eq!(result[1].1, result[2].1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_only_follow_links_passing_the_edge_type_filter(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        const MENTION: EdgeType = 1;
+        let mut page_rank = Pagerank::new(3);
+        page_rank.link_with_type(0, 1, MENTION)?;
+        page_rank.link_with_type(1, 2, MENTION)?;
+        page_rank.link(2, 0)?;
+
+        let config = RankConfig::new(0.85, 0.0001);
+        let result = page_rank.rank_filtered(&config, |edge_type| edge_type == MENTION);
+        assert_eq!(vec![(2, 47.4), (1, 34.1), (0, 18.4)], percentages(result));
+
+        let result = page_rank.rank_filtered(&config, |_| true);
+        assert_eq!(
+            percentages(page_rank.rank_with_config(&config)),
+            percentages(result)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_compute_the_same_ranks_in_chunks() -> Result<(), Box<dyn std::error::Error>> {
+        let mut page_rank = Pagerank::new(5);
+        page_rank.link(0, 1)?;
+        page_rank.link(0, 2)?;
+        page_rank.link(1, 2)?;
+        page_rank.link(2, 2)?;
+        page_rank.link(3, 4)?;
+
+        let config = RankConfig::new(0.85, 0.0001);
+        let mut ranking = page_rank.rank_chunked(&config, 2);
+        let mut chunks = 1;
+        while !ranking.process_chunk() {
+            chunks += 1;
+        }
+
+        assert!(chunks > 3, "Each iteration should take 3 chunks");
+        assert_eq!(
+            percentages(page_rank.rank_with_config(&config)),
+            percentages(ranking.into_ranked())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_compute_the_same_ranks_in_chunks_from_any_initial_distribution(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut page_rank = Pagerank::new(20);
+        for node in 0..19 {
+            page_rank.link(node, node + 1)?;
+            page_rank.link(node, (node * 7) % 20)?;
+        }
+
+        let mut config = RankConfig::new(0.85, 0.0001);
+        config.tie_break = TieBreak::Key;
+        config.no_teleport.insert(3);
+        config.no_emit.insert(5);
+        for initial in [
+            InitialDistribution::Uniform,
+            InitialDistribution::DegreeProportional,
+            InitialDistribution::TeleportProportional,
+            InitialDistribution::Source(4),
+            InitialDistribution::WarmStart(vec![(1, 0.5), (2, 0.25), (42, 0.25)]),
+        ] {
+            config.initial = initial;
+            for chunk_size in [1, 3, 7, 64] {
+                assert_eq!(
+                    percentages(page_rank.rank_with_config(&config)),
+                    percentages(page_rank.rank_chunked(&config, chunk_size).into_ranked())
+                );
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_yield_between_chunks_when_ranking_asynchronously(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut page_rank = Pagerank::new(3);
+        page_rank.link(0, 1)?;
+        page_rank.link(1, 2)?;
+
+        let config = RankConfig::new(0.85, 0.0001);
+        let yields = std::cell::Cell::new(0);
+        let future = page_rank.rank_chunked_async(&config, 1, || {
+            yields.set(yields.get() + 1);
+            std::future::ready(())
+        });
+        let mut future = std::pin::pin!(future);
+        let mut context = std::task::Context::from_waker(std::task::Waker::noop());
+        let result = loop {
+            if let std::task::Poll::Ready(result) =
+                std::future::Future::poll(future.as_mut(), &mut context)
+            {
+                break result;
+            }
+        };
+
+        assert!(yields.get() > 0);
+        let expected = vec![(2, 47.4), (1, 34.1), (0, 18.4)];
+        assert_eq!(expected, percentages(result));
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_only_notify_subscribers_of_significant_score_changes(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut page_rank = Pagerank::new(4);
+        page_rank.link(0, 1)?;
+        page_rank.link(1, 2)?;
+
+        let notified = std::sync::Arc::new(std::sync::Mutex::new(Vec::new()));
+        let mut publisher = ScorePublisher::new();
+        {
+            let notified = notified.clone();
+            publisher.subscribe(0.01, move |deltas| {
+                let keys = deltas.iter().map(|delta| delta.key).collect::<Vec<_>>();
+                notified.lock().unwrap().push(keys);
+            });
+        }
+
+        publisher.publish(&page_rank.rank(0.85, 0.0001));
+        publisher.publish(&page_rank.rank(0.85, 0.0001));
+        page_rank.link(3, 2)?;
+        publisher.publish(&page_rank.rank(0.85, 0.0001));
+
+        let notified = notified.lock().unwrap();
+        assert_eq!(2, notified.len(), "Unchanged scores should not be notified");
+        assert_eq!(3, notified[0].len());
+        assert!(notified[1].contains(&3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_remove_all_the_links_from_a_node() -> Result<(), Box<dyn std::error::Error>> {
+        let mut page_rank = Pagerank::new(3);
+        page_rank.link(0, 1)?;
+        page_rank.link(2, 1)?;
+        page_rank.link(2, 0)?;
+        page_rank.link(2, 2)?;
+
+        assert_eq!(3, page_rank.unlink_all_from(2));
+        assert_eq!(0, page_rank.unlink_all_from(2));
+        page_rank.link(1, 2)?;
+
+        let expected = vec![(2, 47.4), (1, 34.1), (0, 18.4)];
+        assert_rank(&page_rank, &expected, 0.0001);
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_move_a_link_to_another_node() -> Result<(), Box<dyn std::error::Error>> {
+        let mut page_rank = Pagerank::new(3);
+        page_rank.link(0, 2)?;
+        page_rank.link(1, 0)?;
+
+        page_rank.relink(1, 0, 2)?;
+
+        let expected = vec![(2, 57.4), (0, 21.3), (1, 21.3)];
+        assert_eq!(expected, percentages(page_rank.rank(0.85, 0.0001)));
+        assert!(page_rank.relink(1, 0, 2).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_compute_the_strength_centralities() -> Result<(), Box<dyn std::error::Error>> {
+        let mut page_rank = Pagerank::new(3);
+        page_rank.link(0, 2)?;
+        page_rank.link(0, 2)?;
+        page_rank.link(1, 2)?;
+        page_rank.link(2, 1)?;
+
+        assert_eq!(
+            vec![(2, 3.0), (1, 1.0), (0, 0.0)],
+            page_rank.in_strength(false)
+        );
+        assert_eq!(
+            vec![(0, 50.0), (1, 25.0), (2, 25.0)],
+            percentages(page_rank.out_strength(true))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_converge_to_the_same_ranks_from_any_initial_distribution(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut page_rank = Pagerank::new(3);
+        page_rank.link(0, 1)?;
+        page_rank.link(0, 2)?;
+        page_rank.link(1, 2)?;
+        page_rank.link(2, 2)?;
+
+        let expected = vec![(2, 87.9), (1, 7.1), (0, 5.0)];
+        let previous = page_rank.rank(0.85, 0.0001);
+        for initial in [
+            InitialDistribution::Uniform,
+            InitialDistribution::WarmStart(previous),
+            InitialDistribution::DegreeProportional,
+            InitialDistribution::TeleportProportional,
+            InitialDistribution::Source(0),
+            InitialDistribution::Source(42),
+        ] {
+            let mut config = RankConfig::new(0.85, 0.0001);
+            config.initial = initial;
+            assert_eq!(expected, percentages(page_rank.rank_with_config(&config)));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_rank_the_reversed_graph() -> Result<(), Box<dyn std::error::Error>> {
+        let mut page_rank = Pagerank::new(3);
+        page_rank.link(0, 1)?;
+        page_rank.link(1, 2)?;
+
+        let result = page_rank.rank_reversed(&RankConfig::new(0.85, 0.0001));
+
+        let expected = vec![(0, 47.4), (1, 34.1), (2, 18.4)];
+        assert_eq!(expected, percentages(result));
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_compute_the_same_ranks_with_numa_aware_buffers(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut page_rank = Pagerank::new(5);
+        page_rank.link(0, 1)?;
+        page_rank.link(0, 2)?;
+        page_rank.link(1, 2)?;
+        page_rank.link(2, 2)?;
+        page_rank.link(3, 4)?;
+
+        let mut config = RankConfig::new(0.85, 0.0001);
+        let expected = percentages(page_rank.rank_with_config(&config));
+        config.numa_aware = true;
+
+        assert_eq!(expected, percentages(page_rank.rank_with_config(&config)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_compute_the_same_ranks_after_reordering_the_nodes(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        for order in [
+            NodeOrder::Degree,
+            NodeOrder::Bfs,
+            NodeOrder::ReverseCuthillMckee,
+        ] {
+            let mut page_rank = Pagerank::new(5);
+            page_rank.link(3, 4)?;
+            page_rank.link(0, 1)?;
+            page_rank.link(0, 2)?;
+            page_rank.link(1, 2)?;
+            page_rank.link(2, 2)?;
+            let expected = percentages(page_rank.rank(0.85, 0.0001));
+
+            page_rank.reorder(order);
+            assert_eq!(expected, percentages(page_rank.rank(0.85, 0.0001)));
+
+            page_rank.link(4, 0)?;
+            assert_eq!(1, page_rank.unlink_all_from(4));
+            assert_eq!(expected, percentages(page_rank.rank(0.85, 0.0001)));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_compute_the_same_ranks_with_any_number_of_threads(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut page_rank = Pagerank::new(5);
+        page_rank.link(0, 1)?;
+        page_rank.link(0, 2)?;
+        page_rank.link(1, 2)?;
+        page_rank.link(2, 2)?;
+        page_rank.link(3, 4)?;
+
+        let mut config = RankConfig::new(0.85, 0.0001);
+        let expected = percentages(page_rank.rank_with_config(&config));
+        // 0 threads uses the global pool, repeating a count reuses the pool built for it.
+        for threads in [0, 1, 2, 2] {
+            config.threads = Some(threads);
+            assert_eq!(expected, percentages(page_rank.rank_with_config(&config)));
+            assert_eq!(
+                percentages(page_rank.rank_reversed(&RankConfig::new(0.85, 0.0001))),
+                percentages(page_rank.rank_reversed(&config))
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_fail_when_adding_more_nodes_than_the_capacity(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut page_rank = Pagerank::new(2);
+        page_rank.link(0, 1)?;
+
+        assert!(page_rank.link(1, 2).is_err());
+        assert!(page_rank.link(2, 0).is_err());
+        page_rank.link(1, 0)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_bound_the_emitted_scores() -> Result<(), Box<dyn std::error::Error>> {
+        let mut page_rank = Pagerank::new(3);
+        page_rank.link(0, 1)?;
+        page_rank.link(1, 2)?;
+        page_rank.link(2, 1)?;
+
+        let mut config = RankConfig::new(0.85, 0.0001);
+        config.score_floor = Some(0.1);
+        config.score_ceiling = Some(0.4);
+        let ranked = page_rank.rank_with_config(&config);
+
+        assert_eq!(3, ranked.len());
+        assert!(ranked
+            .iter()
+            .all(|&(_, score)| (0.1..=0.4).contains(&score)));
+        assert_eq!(0, ranked[2].0);
+        assert_eq!(0.1, ranked[2].1);
+        assert_eq!(
+            percentages(ranked),
+            percentages(page_rank.rank_chunked(&config, 2).into_ranked())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_extract_the_top_k_subgraph() -> Result<(), Box<dyn std::error::Error>> {
+        let mut page_rank = Pagerank::new(4);
+        page_rank.link(0, 1)?;
+        page_rank.link(1, 2)?;
+        page_rank.link(2, 1)?;
+        page_rank.link(3, 2)?;
+        page_rank.link(0, 3)?;
+
+        let subgraph = page_rank.top_k_subgraph(&RankConfig::new(0.85, 0.0001), 2);
+
+        let keys: Vec<usize> = subgraph.nodes.iter().map(|&(key, _)| key).collect();
+        assert_eq!(vec![2, 1], keys);
+        assert_eq!(page_rank.rank(0.85, 0.0001)[..2], subgraph.nodes[..],);
+        assert_eq!(vec![(1, 2), (2, 1)], subgraph.links);
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_grow_namespaces_on_demand_up_to_their_capacity(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut namespaces = PagerankNamespaces::new(100);
+        namespaces.link(1, 0, 1)?;
+        let initial = namespaces.graph(1).map_or(0, Pagerank::capacity);
+        assert!(initial < 100);
+
+        for node in 1..99 {
+            namespaces.link(1, node, node + 1)?;
+        }
+        assert!(namespaces.link(1, 99, 100).is_err());
+        assert_eq!(Some(100), namespaces.graph(1).map(Pagerank::capacity));
+        assert_eq!(100, namespaces.rank(1, &RankConfig::default()).len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_not_create_a_namespace_whose_first_link_fails(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut namespaces = PagerankNamespaces::new(1);
+        assert!(namespaces.link(1, 0, 1).is_err());
+        assert!(namespaces.graph(1).is_none());
+        assert_eq!(0, namespaces.namespaces().count());
+
+        namespaces.link(1, 0, 0)?;
+        assert_eq!(vec![1], namespaces.namespaces().collect::<Vec<_>>());
+
+        let mut empty = PagerankNamespaces::new(0);
+        assert!(empty.link(1, 0, 0).is_err());
+        assert!(empty.graph(1).is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_rank_namespaces_independently() -> Result<(), Box<dyn std::error::Error>> {
+        let mut namespaces = PagerankNamespaces::with_threads(3, 2);
+        namespaces.link(7, 0, 1)?;
+        namespaces.link(7, 1, 2)?;
+        namespaces.link(9, 0, 1)?;
+        namespaces.link(9, 1, 0)?;
+        assert!(namespaces.link(7, 2, 3).is_err());
+
+        let mut graph = Pagerank::new(3);
+        graph.link(0, 1)?;
+        graph.link(1, 2)?;
+        let config = RankConfig::new(0.85, 0.0001);
+        let ranked = namespaces.rank_all(&config);
+
+        assert_eq!(2, ranked.len());
+        assert_eq!(
+            percentages(graph.rank(0.85, 0.0001)),
+            percentages(ranked[&7].clone())
+        );
+        assert_eq!(
+            vec![(0, 50.0), (1, 50.0)],
+            percentages(namespaces.rank(9, &config))
+        );
+        assert!(namespaces.rank(8, &config).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_stop_ranking_when_the_budget_runs_out() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let mut page_rank = Pagerank::new(3);
+        page_rank.link(0, 1)?;
+        page_rank.link(1, 2)?;
+        page_rank.link(2, 0)?;
+        page_rank.link(2, 1)?;
+        let config = RankConfig::new(0.85, 0.0001);
+
+        // 3 nodes and 4 links make 7 visits per iteration.
+        let budgeted = page_rank.rank_budgeted(&config, 20);
+        assert_eq!(2, budgeted.iterations);
+        assert!(!budgeted.converged);
+        assert!(budgeted.error_bound > 0.0);
+
+        let unlimited = page_rank.rank_budgeted(&config, usize::MAX);
+        assert!(unlimited.converged);
+        assert!(unlimited.error_bound < budgeted.error_bound);
+        assert_eq!(
+            percentages(page_rank.rank(0.85, 0.0001)),
+            percentages(unlimited.ranked)
+        );
+
+        let timed = page_rank.rank_for(&config, std::time::Duration::ZERO);
+        assert_eq!(0, timed.iterations);
+        assert_eq!(2.0, timed.error_bound);
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_bound_the_error_of_every_solver() -> Result<(), Box<dyn std::error::Error>> {
+        let mut page_rank = Pagerank::new(4);
+        page_rank.link(0, 1)?;
+        page_rank.link(1, 2)?;
+        page_rank.link(2, 0)?;
+        page_rank.link(2, 1)?;
+        page_rank.link(3, 2)?;
+        let mut config = RankConfig::new(0.85, 1e-12);
+        config.solver = Solver::Exact;
+        let exact = page_rank.rank_budgeted(&config, usize::MAX);
+        assert_eq!(0.0, exact.error_bound);
+
+        let converged: std::collections::HashMap<usize, f64> = exact.ranked.into_iter().collect();
+        let distance = |ranked: &[(usize, f64)]| -> f64 {
+            ranked
+                .iter()
+                .map(|(key, score)| (score - converged[key]).abs())
+                .sum()
+        };
+        config.tolerance = 0.0001;
+        for solver in [
+            Solver::PowerIteration,
+            Solver::GaussSeidel,
+            Solver::Condensation,
+        ] {
+            config.solver = solver;
+            for max_edge_visits in [18, 36, usize::MAX] {
+                let result = page_rank.rank_budgeted(&config, max_edge_visits);
+                assert!(
+                    distance(&result.ranked) <= result.error_bound,
+                    "{:?} within {} visits",
+                    solver,
+                    max_edge_visits
+                );
+                assert!(result.error_bound <= 2.0);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_report_score_trends() {
+        let mut history = ScoreHistory::new(3);
+        assert_eq!(None, history.slope(1));
+
+        history.record(&[(1, 0.9), (2, 0.1)]);
+        history.record(&[(1, 0.6), (2, 0.2), (3, 0.2)]);
+        history.record(&[(1, 0.5), (2, 0.3), (3, 0.2)]);
+        history.record(&[(1, 0.4), (2, 0.4), (3, 0.2)]);
+
+        assert_eq!(3, history.len());
+        assert_eq!(vec![0.6, 0.5, 0.4], history.scores_of(1));
+        assert!(approx_eq!(
+            f64,
+            0.1,
+            history.slope(2).unwrap(),
+            epsilon = 1e-12
+        ));
+        assert_eq!(Some(Trend::Falling), history.trend(1, 0.01));
+        assert_eq!(Some(Trend::Rising), history.trend(2, 0.01));
+        assert_eq!(Some(Trend::Flat), history.trend(3, 0.01));
+        assert_eq!(
+            vec![2],
+            history
+                .trending(5)
+                .iter()
+                .map(|&(key, _)| key)
+                .collect::<Vec<_>>()
+        );
+    }
+
+    #[test]
+    fn test_should_break_ties_deterministically() -> Result<(), Box<dyn std::error::Error>> {
+        let mut page_rank = Pagerank::new(6);
+        for key in [5, 3, 1, 4, 0, 2] {
+            page_rank.link(key, key)?;
+        }
+
+        let mut config = RankConfig::new(0.85, 0.0001);
+        config.tie_break = TieBreak::Key;
+        let keys = |ranked: Vec<(usize, f64)>| -> Vec<usize> {
+            ranked.into_iter().map(|(key, _)| key).collect()
+        };
+        assert_eq!(
+            vec![0, 1, 2, 3, 4, 5],
+            keys(page_rank.rank_with_config(&config))
+        );
+
+        config.tie_break = TieBreak::SeededHash(42);
+        let seeded = keys(page_rank.rank_with_config(&config));
+        assert_eq!(seeded, keys(page_rank.rank_with_config(&config)));
+        assert_eq!(
+            seeded,
+            keys(page_rank.rank_chunked(&config, 4).into_ranked())
+        );
+        config.tie_break = TieBreak::SeededHash(7);
+        assert_ne!(seeded, keys(page_rank.rank_with_config(&config)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_break_long_runs_of_ties_in_chunks() -> Result<(), Box<dyn std::error::Error>> {
+        // The 40 targets are symmetric, but add up the shares of the sources in rotated orders,
+        // so their scores form a long run of ties that differ in their last bits.
+        let mut page_rank = Pagerank::new(200);
+        let sources = [100, 101, 102, 103, 104, 105, 106];
+        for (extra, &source) in sources.iter().enumerate() {
+            for filler in 0..3 * extra {
+                page_rank.link(source, 150 + filler)?;
+            }
+        }
+        for target in 0..40 {
+            for offset in 0..sources.len() {
+                page_rank.link(sources[(target + offset) % sources.len()], target)?;
+            }
+            page_rank.link(target, sources[target % sources.len()])?;
+        }
+
+        let keys = |ranked: Vec<(usize, f64)>| -> Vec<usize> {
+            ranked.into_iter().map(|(key, _)| key).collect()
+        };
+        let mut config = RankConfig::new(0.85, 0.0001);
+        for tie_break in [TieBreak::Key, TieBreak::SeededHash(42)] {
+            config.tie_break = tie_break;
+            let expected = keys(page_rank.rank_with_config(&config));
+            for chunk_size in [1, 3, 7, 64] {
+                assert_eq!(
+                    expected,
+                    keys(page_rank.rank_chunked(&config, chunk_size).into_ranked()),
+                    "{:?} in chunks of {}",
+                    tie_break,
+                    chunk_size
+                );
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_grow_the_capacity_keeping_the_graph() -> Result<(), Box<dyn std::error::Error>> {
+        let mut page_rank = Pagerank::new(2);
+        page_rank.link(0, 1)?;
+        assert!(page_rank.link(1, 2).is_err());
+
+        page_rank.grow_capacity(1)?;
+        assert_eq!(3, page_rank.capacity());
+        page_rank.link(1, 2)?;
+        assert!(page_rank.link(2, 3).is_err());
+        assert!(page_rank.grow_capacity(usize::MAX).is_err());
+        assert_eq!(3, page_rank.capacity());
+
+        let mut expected = Pagerank::new(3);
+        expected.link(0, 1)?;
+        expected.link(1, 2)?;
+        assert_eq!(
+            percentages(expected.rank(0.85, 0.0001)),
+            percentages(page_rank.rank(0.85, 0.0001))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_link_consecutive_sources_after_renumbering_the_nodes(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut page_rank = Pagerank::new(3);
+        page_rank.link(5, 1)?;
+        page_rank.clear();
+        page_rank.link(1, 5)?;
+        page_rank.link(5, 2)?;
+        page_rank.reorder(NodeOrder::Degree);
+        page_rank.link(5, 1)?;
+
+        let mut expected = Pagerank::new(3);
+        expected.link(1, 5)?;
+        expected.link(5, 2)?;
+        expected.link(5, 1)?;
+        assert_eq!(
+            percentages(expected.rank(0.85, 0.0001)),
+            percentages(page_rank.rank(0.85, 0.0001))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_summarize_and_dump_the_graph() -> Result<(), Box<dyn std::error::Error>> {
+        let mut page_rank = Pagerank::new(4);
+        page_rank.link(7, 8)?;
+        page_rank.link(7, 9)?;
+
+        assert_eq!(
+            "Pagerank: 3 nodes, 2 links, 2 dangling nodes, capacity 4",
+            page_rank.to_string()
+        );
+
+        let mut dump = Vec::new();
+        page_rank.dump(&mut dump, 2)?;
+        assert_eq!(
+            "Pagerank: 3 nodes, 2 links, 2 dangling nodes, capacity 4\n\
+             0 (key 7): out links 2, in links [], in link types []\n\
+             1 (key 8): out links 0, in links [0], in link types [0]\n\
+             ... 1 more nodes\n",
+            String::from_utf8(dump)?
+        );
+
+        // The incoming links of a hub are truncated too.
+        page_rank.link(9, 8)?;
+        page_rank.link(8, 8)?;
+        let mut dump = Vec::new();
+        page_rank.dump(&mut dump, 2)?;
+        assert_eq!(
+            "Pagerank: 3 nodes, 4 links, 0 dangling nodes, capacity 4\n\
+             0 (key 7): out links 2, in links [], in link types []\n\
+             1 (key 8): out links 1, in links [0, 2] (+1 more), in link types [0, 0] (+1 more)\n\
+             ... 1 more nodes\n",
+            String::from_utf8(dump)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_trace_links_back_to_their_provenance() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let mut page_rank = Pagerank::new(5);
+        page_rank.link(0, 4)?;
+        page_rank.link_with_provenance(1, 4, DEFAULT_EDGE_TYPE, 7)?;
+        page_rank.link_with_provenance(2, 4, DEFAULT_EDGE_TYPE, 7)?;
+        page_rank.link_with_provenance(3, 4, DEFAULT_EDGE_TYPE, 9)?;
+        page_rank.link_with_provenance(1, 3, DEFAULT_EDGE_TYPE, 9)?;
+
+        assert_eq!(
+            vec![(NO_PROVENANCE, 1), (7, 2), (9, 1)],
+            page_rank.provenance_summary(4)
+        );
+
+        page_rank.unlink_all_from(2);
+        page_rank.relink(1, 3, 0)?;
+        page_rank.reorder(NodeOrder::Degree);
+
+        let mut in_links = page_rank.in_link_provenance(4);
+        in_links.sort_unstable();
+        assert_eq!(vec![(0, NO_PROVENANCE), (1, 7), (3, 9)], in_links);
+        assert_eq!(vec![(1, 9)], page_rank.in_link_provenance(0));
+        assert!(page_rank.in_link_provenance(42).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_reject_the_reserved_provenance_tag() -> Result<(), Box<dyn std::error::Error>> {
+        let mut page_rank = Pagerank::new(3);
+        page_rank.link_with_provenance(0, 1, DEFAULT_EDGE_TYPE, 1)?;
+
+        let result = page_rank.link_with_provenance(1, 2, DEFAULT_EDGE_TYPE, NO_PROVENANCE);
+
+        assert!(matches!(result, Err(PagerankError::ProvenanceError(_))));
+        assert!(page_rank.in_link_provenance(2).is_empty());
+        assert_eq!(vec![(0, 1)], page_rank.in_link_provenance(1));
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_explain_a_score_with_its_top_paths() -> Result<(), Box<dyn std::error::Error>> {
+        let mut page_rank = Pagerank::new(5);
+        page_rank.link(0, 1)?;
+        page_rank.link(1, 4)?;
+        page_rank.link(2, 4)?;
+        page_rank.link(2, 3)?;
+        page_rank.link(3, 4)?;
+        page_rank.link(4, 0)?;
+        let config = RankConfig::new(0.85, 0.0001);
+
+        let paths = page_rank.top_paths_to(&config, 4, 3, 2);
+
+        let ranked: std::collections::HashMap<usize, f64> =
+            page_rank.rank_with_config(&config).into_iter().collect();
+        assert_eq!(3, paths.len());
+        assert_eq!(vec![1, 4], paths[0].0);
+        assert!(approx_eq!(
+            f64,
+            ranked[&1] * 0.85,
+            paths[0].1,
+            epsilon = 1e-12
+        ));
+        assert_eq!(vec![0, 1, 4], paths[1].0);
+        assert!(approx_eq!(
+            f64,
+            ranked[&0] * 0.85 * 0.85,
+            paths[1].1,
+            epsilon = 1e-12
+        ));
+        assert!(paths.windows(2).all(|pair| pair[0].1 >= pair[1].1));
+        assert!(page_rank.top_paths_to(&config, 42, 3, 2).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_drop_negligible_scores() -> Result<(), Box<dyn std::error::Error>> {
+        let mut page_rank = Pagerank::new(4);
+        page_rank.link(0, 1)?;
+        page_rank.link(1, 2)?;
+        page_rank.link(2, 1)?;
+        page_rank.link(3, 1)?;
+
+        let mut config = RankConfig::new(0.85, 0.0001);
+        let full = page_rank.rank_with_config(&config);
+        config.drop_below = Some(0.1);
+        let pruned = page_rank.rank_pruned(&config);
+
+        assert_eq!(2, pruned.dropped);
+        assert_eq!(
+            percentages(full[..2].to_vec()),
+            percentages(pruned.ranked.clone())
+        );
+        let dropped_mass: f64 = full[2..].iter().map(|&(_, score)| score).sum();
+        assert!(approx_eq!(
+            f64,
+            dropped_mass,
+            pruned.dropped_mass,
+            epsilon = 1e-6
+        ));
+        assert_eq!(2, page_rank.rank_with_config(&config).len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_every_solver_should_converge_to_the_same_scores(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut page_rank = Pagerank::new(8);
+        page_rank.link(0, 1)?;
+        page_rank.link(1, 2)?;
+        page_rank.link(2, 0)?;
+        page_rank.link(2, 2)?;
+        page_rank.link(3, 0)?;
+        page_rank.link(3, 4)?;
+        page_rank.link_with_type(4, 5, 1)?;
+        page_rank.link(5, 4)?;
+        page_rank.link(6, 5)?;
+
+        let mut config = RankConfig::new(0.85, 1e-10);
+        config.no_teleport.insert(3);
+        let expected = page_rank.rank_with_config(&config);
+        let expected_filtered = page_rank.rank_filtered(&config, |edge_type| edge_type == 0);
+
+        for solver in [
+            Solver::GaussSeidel,
+            Solver::Condensation,
+            Solver::Exact,
+            Solver::Auto,
+        ] {
+            config.solver = solver;
+            let same_scores = |expected: &[(usize, f64)], result: Vec<(usize, f64)>| {
+                let result: std::collections::HashMap<usize, f64> = result.into_iter().collect();
+                expected.len() == result.len()
+                    && expected
+                        .iter()
+                        .all(|(key, score)| approx_eq!(f64, *score, result[key], epsilon = 1e-6))
+            };
+
+            assert!(
+                same_scores(&expected, page_rank.rank_with_config(&config)),
+                "{:?}",
+                solver
+            );
+            assert!(
+                same_scores(
+                    &expected_filtered,
+                    page_rank.rank_filtered(&config, |edge_type| edge_type == 0)
+                ),
+                "{:?}",
+                solver
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_exact_solver_should_iterate_on_graphs_above_its_node_cap(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let nodes = EXACT_MAX_NODES + 1;
+        let mut page_rank = Pagerank::new(nodes);
+        for from in 1..nodes {
+            page_rank.link(from, from - 1)?;
+        }
+
+        let mut config = RankConfig::new(0.85, 0.0001);
+        config.solver = Solver::Exact;
+        let result = page_rank.rank_budgeted(&config, usize::MAX);
+
+        // Elimination solves in a single step, power iteration needs many on a chain.
+        assert!(result.iterations > 1);
+        assert_eq!(nodes, result.ranked.len());
+        Ok(())
+    }
+
+    #[test]
+    fn test_auto_solver_should_follow_the_graph_structure() -> Result<(), Box<dyn std::error::Error>>
+    {
+        let mut config = RankConfig::new(0.85, 0.0001);
+        config.solver = Solver::Auto;
+        config.threads = Some(1);
+
+        let mut small = Pagerank::new(3);
+        small.link(0, 1)?;
+        small.link(1, 2)?;
+        small.link(2, 0)?;
+        assert_eq!(Solver::Exact, small.auto_solver(&config));
+
+        let mut chain = Pagerank::new(3_000);
+        let mut ring = Pagerank::new(3_000);
+        for from in 0..3_000 {
+            if from + 1 < 3_000 {
+                chain.link(from, from + 1)?;
+            }
+            ring.link(from, (from + 1) % 3_000)?;
+        }
+        assert_eq!(Solver::Condensation, chain.auto_solver(&config));
+        assert_eq!(Solver::GaussSeidel, ring.auto_solver(&config));
+
+        config.numa_aware = true;
+        assert_eq!(Solver::PowerIteration, ring.auto_solver(&config));
+        config.numa_aware = false;
+        config.following_prob = 1.0;
+        assert_eq!(Solver::PowerIteration, small.auto_solver(&config));
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_break_ties_between_symmetric_nodes_despite_rounding(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        // Nodes 0 and 1 are symmetric, but receive the shares of sources with different out
+        // degrees in opposite orders, so their scores are added up in a different order.
+        let mut page_rank = Pagerank::new(30);
+        let sources = [10, 11, 12, 13, 14, 15];
+        let out_degrees = [5, 6, 10, 15, 17, 20];
+        for (&source, &degree) in sources.iter().zip(&out_degrees) {
+            for filler in 2..degree {
+                page_rank.link(source, 16 + filler)?;
+            }
+        }
+        for &source in &sources {
+            page_rank.link(source, 0)?;
+        }
+        for &source in sources.iter().rev() {
+            page_rank.link(source, 1)?;
+        }
+        for &source in &sources {
+            page_rank.link(0, source)?;
+            page_rank.link(1, source)?;
+        }
+
+        let mut config = RankConfig::new(0.85, 1e-12);
+        config.tie_break = TieBreak::Key;
+        let ranked = page_rank.rank_with_config(&config);
+        let position = ranked
+            .iter()
+            .position(|&(key, _)| key == 0 || key == 1)
+            .unwrap();
+
+        assert_eq!((0, 1), (ranked[position].0, ranked[position + 1].0));
+        // Other paths add the scores in other orders, so only the keys must match exactly.
+        let same_ranking = |expected: &[(usize, f64)], result: &[(usize, f64)]| {
+            expected.len() == result.len()
+                && expected.iter().zip(result).all(|(a, b)| {
+                    a.0 == b.0 && approx_eq!(f64, a.1, b.1, epsilon = TIE_EPSILON * a.1)
+                })
+        };
+        assert!(same_ranking(
+            &ranked[..=position],
+            &page_rank.rank_top_k(&config, position + 1).top
+        ));
+        assert!(same_ranking(
+            &ranked,
+            &page_rank.rank_chunked(&config, 4).into_ranked()
+        ));
         Ok(())
     }
 }
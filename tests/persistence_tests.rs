@@ -0,0 +1,70 @@
+#[cfg(test)]
+mod tests {
+    use pagerank_rs::errors::PagerankError;
+    use pagerank_rs::{InitialDistribution, Pagerank, RankConfig, ScoreSnapshot};
+
+    const EDGE_LIST: &str = "# A comment\n\
+                             0 1\n\
+                             \n\
+                             1,2\n\
+                             2\t0\t3\n";
+
+    #[test]
+    fn test_should_round_trip_a_score_snapshot() -> Result<(), Box<dyn std::error::Error>> {
+        let mut page_rank = Pagerank::new(3);
+        page_rank.load_edge_list(EDGE_LIST.as_bytes())?;
+        let mut reordered = Pagerank::new(3);
+        reordered.load_edge_list("2 0 3\n1 2\n0 1\n".as_bytes())?;
+        assert_eq!(page_rank.graph_hash(), reordered.graph_hash());
+        reordered.link(0, 2)?;
+        assert_ne!(page_rank.graph_hash(), reordered.graph_hash());
+
+        let config = RankConfig::new(0.85, 0.0001);
+        let snapshot = ScoreSnapshot::new(&page_rank, &config, page_rank.rank_with_config(&config));
+        let mut written = Vec::new();
+        snapshot.write(&mut written)?;
+
+        assert_eq!(snapshot, ScoreSnapshot::read(written.as_slice())?);
+        let written = String::from_utf8(written)?;
+        let last_line = written.trim_end().rfind('\n').map_or(0, |end| end + 1);
+        let truncated = ScoreSnapshot::read(&written.as_bytes()[..last_line]);
+        assert!(matches!(truncated, Err(PagerankError::ParseError(_))));
+        let newer = written.replacen("snapshot 1", "snapshot 2", 1);
+        let newer = ScoreSnapshot::read(newer.as_bytes());
+        assert!(matches!(newer, Err(PagerankError::SnapshotError(_))));
+        let older = written.replacen("snapshot 1", "snapshot 0", 1);
+        let older = ScoreSnapshot::read(older.as_bytes());
+        assert!(matches!(older, Err(PagerankError::SnapshotError(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_refuse_to_warm_start_with_incompatible_parameters(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let mut page_rank = Pagerank::new(3);
+        page_rank.load_edge_list(EDGE_LIST.as_bytes())?;
+        let mut config = RankConfig::new(0.85, 0.0001);
+        config.no_emit.insert(1);
+        let snapshot = ScoreSnapshot::new(&page_rank, &config, page_rank.rank_with_config(&config));
+
+        let mut next = RankConfig::new(0.85, 0.000001);
+        next.no_emit.insert(1);
+        snapshot.warm_start(&mut next)?;
+        assert_eq!(
+            InitialDistribution::WarmStart(snapshot.scores.clone()),
+            next.initial
+        );
+
+        let mut drifted = RankConfig::new(0.9, 0.0001);
+        drifted.no_emit.insert(1);
+        assert!(matches!(
+            snapshot.warm_start(&mut drifted),
+            Err(PagerankError::SnapshotError(_))
+        ));
+        drifted.following_prob = 0.85;
+        drifted.no_emit.insert(2);
+        assert!(!snapshot.is_compatible(&drifted));
+        assert_eq!(InitialDistribution::Uniform, drifted.initial);
+        Ok(())
+    }
+}
@@ -0,0 +1,24 @@
+#![cfg(feature = "proptest")]
+
+use float_cmp::approx_eq;
+use pagerank_rs::arbitrary::pagerank_strategy;
+use pagerank_rs::Pagerank;
+use proptest::prelude::*;
+
+proptest! {
+    #[test]
+    fn test_scores_always_sum_to_one(page_rank in pagerank_strategy(16, 64)) {
+        let result = page_rank.rank(0.85, 0.0001);
+        prop_assume!(!result.is_empty());
+
+        let total: f64 = result.iter().map(|&(_, rank)| rank).sum();
+        prop_assert!(approx_eq!(f64, total, 1.0, epsilon = 0.0001));
+    }
+
+    #[test]
+    fn test_arbitrary_graphs_can_be_ranked(page_rank in any::<Pagerank>()) {
+        let result = page_rank.rank(0.85, 0.0001);
+
+        prop_assert!(result.iter().all(|&(_, rank)| rank.is_finite() && rank >= 0.0));
+    }
+}
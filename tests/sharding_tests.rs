@@ -0,0 +1,141 @@
+#[cfg(test)]
+mod tests {
+    use pagerank_rs::{shard_of, Pagerank, PagerankShard, RankConfig, ShardExchange};
+    use std::sync::mpsc::{channel, Receiver, Sender};
+
+    enum Message {
+        Scores(Vec<(usize, f64)>),
+        Sum(f64),
+    }
+
+    struct ChannelExchange {
+        shard: usize,
+        senders: Vec<Sender<Message>>,
+        receiver: Receiver<Message>,
+    }
+
+    impl ChannelExchange {
+        fn for_shards(shard_count: usize) -> Vec<ChannelExchange> {
+            let (senders, receivers): (Vec<_>, Vec<_>) =
+                (0..shard_count).map(|_| channel()).unzip();
+            receivers
+                .into_iter()
+                .enumerate()
+                .map(|(shard, receiver)| ChannelExchange {
+                    shard,
+                    senders: senders.clone(),
+                    receiver,
+                })
+                .collect()
+        }
+
+        fn others(&self) -> impl Iterator<Item = (usize, &Sender<Message>)> {
+            let shard = self.shard;
+            self.senders
+                .iter()
+                .enumerate()
+                .filter(move |&(other, _)| other != shard)
+        }
+    }
+
+    impl ShardExchange for ChannelExchange {
+        fn exchange(&mut self, mut outgoing: Vec<Vec<(usize, f64)>>) -> Vec<(usize, f64)> {
+            for (other, sender) in self.others() {
+                let scores = std::mem::take(&mut outgoing[other]);
+                sender.send(Message::Scores(scores)).unwrap();
+            }
+
+            let mut incoming = Vec::new();
+            for _ in 1..self.senders.len() {
+                match self.receiver.recv().unwrap() {
+                    Message::Scores(scores) => incoming.extend(scores),
+                    Message::Sum(_) => panic!("Shards are out of sync"),
+                }
+            }
+            incoming
+        }
+
+        fn sum(&mut self, value: f64) -> f64 {
+            for (_, sender) in self.others() {
+                sender.send(Message::Sum(value)).unwrap();
+            }
+
+            let mut sum = value;
+            for _ in 1..self.senders.len() {
+                match self.receiver.recv().unwrap() {
+                    Message::Sum(other) => sum += other,
+                    Message::Scores(_) => panic!("Shards are out of sync"),
+                }
+            }
+            sum
+        }
+    }
+
+    #[test]
+    fn test_should_rank_a_sharded_graph_like_a_single_graph(
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let links = [
+            (1, 2),
+            (2, 1),
+            (3, 0),
+            (3, 1),
+            (4, 3),
+            (4, 1),
+            (4, 5),
+            (5, 4),
+            (5, 1),
+            (6, 1),
+            (6, 4),
+            (7, 1),
+            (7, 4),
+            (8, 1),
+            (8, 4),
+            (9, 4),
+            (10, 4),
+        ];
+        let shard_count = 2;
+        let mut page_rank = Pagerank::new(11);
+        let mut shards: Vec<_> = (0..shard_count)
+            .map(|shard| PagerankShard::new(shard, shard_count, 11))
+            .collect();
+        for (from, to) in links {
+            page_rank.link(from, to)?;
+            shards[shard_of(from, shard_count)].link(from, to)?;
+        }
+
+        let handles: Vec<_> = shards
+            .into_iter()
+            .zip(ChannelExchange::for_shards(shard_count))
+            .map(|(mut shard, mut exchange)| {
+                std::thread::spawn(move || {
+                    shard
+                        .rank(&RankConfig::new(0.85, 0.0001), &mut exchange)
+                        .unwrap()
+                })
+            })
+            .collect();
+        let mut result: Vec<_> = handles
+            .into_iter()
+            .flat_map(|handle| handle.join().unwrap())
+            .collect();
+        result.sort_by_key(|&(key, _)| key);
+
+        let mut expected = page_rank.rank(0.85, 0.0001);
+        expected.sort_by_key(|&(key, _)| key);
+        assert_eq!(expected.len(), result.len());
+        for ((expected_key, expected_rank), (key, rank)) in expected.into_iter().zip(result) {
+            assert_eq!(expected_key, key);
+            assert!((expected_rank - rank).abs() < 0.0001);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_reject_links_from_nodes_owned_by_other_shards() {
+        let key = 42;
+        let other_shard = (shard_of(key, 2) + 1) % 2;
+        let mut shard = PagerankShard::new(other_shard, 2, 10);
+
+        assert!(shard.link(key, 1).is_err());
+    }
+}
@@ -0,0 +1,57 @@
+#[cfg(test)]
+mod tests {
+    use float_cmp::approx_eq;
+    use pagerank_rs::errors::PagerankError;
+    use pagerank_rs::{Pagerank, RankConfig, TieBreak};
+
+    #[test]
+    fn test_should_rank_the_top_k_of_an_edge_list() -> Result<(), Box<dyn std::error::Error>> {
+        let edges = "0 1\n1 2\n2 1\n3 1\n";
+        let mut config = RankConfig::new(0.85, 0.0001);
+        config.tie_break = TieBreak::Key;
+
+        let result = Pagerank::rank_top_k_edge_list(edges.as_bytes(), 4, &config, 2)?;
+
+        let mut page_rank = Pagerank::new(4);
+        page_rank.load_edge_list(edges.as_bytes())?;
+        let full = page_rank.rank_with_config(&config);
+        assert_eq!(full[..2].to_vec(), result.top);
+        assert_eq!(4, result.nodes);
+        assert_eq!(full[0].1, result.max);
+        assert_eq!(full[3].1, result.min);
+        assert!((result.mean - 0.25).abs() < 1e-9);
+        assert!((result.top_mass - full[0].1 - full[1].1).abs() < 1e-12);
+
+        let too_small = Pagerank::rank_top_k_edge_list(edges.as_bytes(), 3, &config, 2);
+        assert!(matches!(too_small, Err(PagerankError::CapacityError(_))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_should_keep_only_the_top_k_scores() -> Result<(), Box<dyn std::error::Error>> {
+        let mut page_rank = Pagerank::new(6);
+        page_rank.link(0, 1)?;
+        page_rank.link(1, 2)?;
+        page_rank.link(2, 0)?;
+        page_rank.link(3, 0)?;
+        page_rank.link(4, 5)?;
+
+        let mut config = RankConfig::new(0.85, 0.0001);
+        config.tie_break = TieBreak::Key;
+        config.drop_below = Some(0.1);
+        let full = page_rank.rank_with_config(&config);
+        let result = page_rank.rank_top_k(&config, 3);
+
+        assert_eq!(full[..3].to_vec(), result.top);
+        assert_eq!(full.len(), result.nodes);
+        assert_eq!(full[0].1, result.max);
+        assert_eq!(full[full.len() - 1].1, result.min);
+        let mean = full.iter().map(|&(_, score)| score).sum::<f64>() / full.len() as f64;
+        assert!(approx_eq!(f64, mean, result.mean, epsilon = 1e-12));
+
+        let everything = page_rank.rank_top_k(&config, 100);
+        assert_eq!(full, everything.top);
+        assert!(page_rank.rank_top_k(&config, 0).top.is_empty());
+        Ok(())
+    }
+}